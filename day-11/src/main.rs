@@ -0,0 +1,221 @@
+use intcode::{IntcodeComputer, IntcodeOperation, Program};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::process;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Color {
+    Black,
+    White,
+}
+
+impl Color {
+    fn from_num(num: i128) -> Result<Color, String> {
+        match num {
+            0 => Ok(Color::Black),
+            1 => Ok(Color::White),
+            n => Err(format!("invalid color {}", n)),
+        }
+    }
+
+    fn to_num(self) -> i128 {
+        match self {
+            Color::Black => 0,
+            Color::White => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Heading {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Heading {
+    /// Turns left for 0 and right for 1
+    fn turn(self, direction: i128) -> Result<Heading, String> {
+        let headings = [Heading::Up, Heading::Right, Heading::Down, Heading::Left];
+        let current = headings.iter().position(|h| *h == self).unwrap();
+        match direction {
+            0 => Ok(headings[(current + 3) % 4]),
+            1 => Ok(headings[(current + 1) % 4]),
+            n => Err(format!("invalid turn {}", n)),
+        }
+    }
+
+    /// Position one panel ahead, with y increasing downwards
+    fn step(self, (x, y): (i64, i64)) -> (i64, i64) {
+        match self {
+            Heading::Up => (x, y - 1),
+            Heading::Right => (x + 1, y),
+            Heading::Down => (x, y + 1),
+            Heading::Left => (x - 1, y),
+        }
+    }
+}
+
+/// Hull-painting robot run by an Intcode program. Each cycle the program reads
+/// the color of the current panel, then outputs a color to paint it and a
+/// direction to turn before moving forward one panel.
+struct Robot {
+    brain: IntcodeComputer,
+    position: (i64, i64),
+    heading: Heading,
+    hull: HashMap<(i64, i64), Color>,
+    /// Panels painted at least once
+    painted: HashSet<(i64, i64)>,
+}
+
+impl Robot {
+    pub fn new(program: &Program, start: Color) -> Robot {
+        let mut hull = HashMap::new();
+        hull.insert((0, 0), start);
+        Robot {
+            brain: program.spawn(vec![], true),
+            position: (0, 0),
+            heading: Heading::Up,
+            hull,
+            painted: HashSet::new(),
+        }
+    }
+
+    fn color_at(&self, position: (i64, i64)) -> Color {
+        *self.hull.get(&position).unwrap_or(&Color::Black)
+    }
+
+    /// Paints until the program halts
+    pub fn run(&mut self) -> Result<(), String> {
+        loop {
+            // The camera only ever shows the panel the robot is on
+            self.brain.inputs.clear();
+            self.brain
+                .inputs
+                .push(self.color_at(self.position).to_num());
+
+            let color = match self.next_output()? {
+                Some(color) => Color::from_num(color)?,
+                None => return Ok(()),
+            };
+            let turn = self
+                .next_output()?
+                .ok_or_else(|| "halted between paint and turn outputs".to_string())?;
+
+            self.hull.insert(self.position, color);
+            self.painted.insert(self.position);
+            self.heading = self.heading.turn(turn)?;
+            self.position = self.heading.step(self.position);
+        }
+    }
+
+    fn next_output(&mut self) -> Result<Option<i128>, String> {
+        match self.brain.try_run().map_err(|err| err.to_string())? {
+            IntcodeOperation::Halt => Ok(None),
+            _ => Ok(self.brain.outputs.pop()),
+        }
+    }
+
+    /// Draws the white panels, with `#` for white and `.` for black
+    pub fn render(&self) -> String {
+        let white: Vec<&(i64, i64)> = self
+            .hull
+            .iter()
+            .filter(|(_, color)| **color == Color::White)
+            .map(|(position, _)| position)
+            .collect();
+        if white.is_empty() {
+            return String::new();
+        }
+        let min_x = white.iter().map(|(x, _)| *x).min().unwrap();
+        let max_x = white.iter().map(|(x, _)| *x).max().unwrap();
+        let min_y = white.iter().map(|(_, y)| *y).min().unwrap();
+        let max_y = white.iter().map(|(_, y)| *y).max().unwrap();
+        (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| match self.color_at((x, y)) {
+                        Color::White => '#',
+                        Color::Black => '.',
+                    })
+                    .chain(std::iter::once('\n'))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+}
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: day-11 <input file>");
+            process::exit(1);
+        }
+    };
+    let program = match Program::load(&path) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    };
+
+    let mut robot = Robot::new(&program, Color::Black);
+    robot.run().unwrap();
+    println!("Part 1 answer: {}", robot.painted.len());
+
+    let mut robot_2 = Robot::new(&program, Color::White);
+    robot_2.run().unwrap();
+    println!("Part 2 answer:\n{}", robot_2.render());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Program that reads each panel into consecutive cells starting at 100
+    /// and answers with a fixed list of paint and turn outputs
+    fn scripted(pairs: &[(i128, i128)]) -> Program {
+        let mut intcodes = Vec::new();
+        for (idx, (color, turn)) in pairs.iter().enumerate() {
+            intcodes.extend(vec![3, 100 + idx as i128, 104, *color, 104, *turn]);
+        }
+        intcodes.push(99);
+        Program::new(intcodes)
+    }
+
+    #[test]
+    fn test_sample_input() {
+        let program = scripted(&[(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)]);
+        let mut robot = Robot::new(&program, Color::Black);
+        robot.run().unwrap();
+        assert_eq!(robot.painted.len(), 6);
+        assert_eq!(robot.position, (0, -1));
+        assert_eq!(robot.heading, Heading::Left);
+        assert_eq!(robot.render(), "..#\n..#\n##.\n");
+
+        // Only the fifth panel was one the robot had already painted white
+        let seen: Vec<i128> = (100..107).map(|addr| robot.brain.intcodes[addr]).collect();
+        assert_eq!(seen, vec![0, 0, 0, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_start_on_white() {
+        let program = scripted(&[(0, 1), (1, 1)]);
+        let mut robot = Robot::new(&program, Color::White);
+        robot.run().unwrap();
+        assert_eq!(robot.brain.intcodes[100], 1);
+        assert_eq!(robot.render(), "#\n");
+        assert_eq!(robot.painted, vec![(0, 0), (1, 0)].into_iter().collect());
+    }
+
+    #[test]
+    fn test_unpaired_output() {
+        let program = Program::new(vec![3, 100, 104, 1, 99]);
+        let mut robot = Robot::new(&program, Color::Black);
+        assert!(robot.run().is_err());
+        assert!(robot.painted.is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+use intcode::{IntcodeComputer, IntcodeOperation, Program};
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+impl Tile {
+    fn from_num(num: i128) -> Result<Tile, String> {
+        match num {
+            0 => Ok(Tile::Empty),
+            1 => Ok(Tile::Wall),
+            2 => Ok(Tile::Block),
+            3 => Ok(Tile::Paddle),
+            4 => Ok(Tile::Ball),
+            n => Err(format!("invalid tile {}", n)),
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Tile::Empty => ' ',
+            Tile::Wall => '|',
+            Tile::Block => '#',
+            Tile::Paddle => '-',
+            Tile::Ball => 'o',
+        }
+    }
+}
+
+/// Tiles drawn so far and the last score shown
+#[derive(Debug, Default)]
+struct Screen {
+    tiles: HashMap<(i64, i64), Tile>,
+    score: i128,
+}
+
+impl Screen {
+    /// Draws an `(x, y, tile)` triple, where `(-1, 0)` sets the score instead
+    fn draw(&mut self, x: i128, y: i128, value: i128) -> Result<(), String> {
+        if (x, y) == (-1, 0) {
+            self.score = value;
+        } else {
+            self.tiles
+                .insert((x as i64, y as i64), Tile::from_num(value)?);
+        }
+        Ok(())
+    }
+
+    fn block_count(&self) -> usize {
+        self.tiles
+            .values()
+            .filter(|tile| **tile == Tile::Block)
+            .count()
+    }
+
+    /// x position of the first tile of a kind
+    fn find(&self, kind: Tile) -> Option<i64> {
+        self.tiles
+            .iter()
+            .find(|(_, tile)| **tile == kind)
+            .map(|((x, _), _)| *x)
+    }
+
+    pub fn render(&self) -> String {
+        let mut screen = format!("Score: {}\n", self.score);
+        if self.tiles.is_empty() {
+            return screen;
+        }
+        let max_x = self.tiles.keys().map(|(x, _)| *x).max().unwrap();
+        let max_y = self.tiles.keys().map(|(_, y)| *y).max().unwrap();
+        for y in 0..=max_y {
+            screen.extend(
+                (0..=max_x).map(|x| self.tiles.get(&(x, y)).unwrap_or(&Tile::Empty).to_char()),
+            );
+            screen.push('\n');
+        }
+        screen
+    }
+}
+
+/// Moves the joystick whenever the game asks for it, with -1 for left, 0 for
+/// neutral and 1 for right. `None` stops the game.
+trait Controller {
+    fn joystick(&mut self, screen: &Screen) -> Option<i128>;
+}
+
+/// Plays back a fixed list of moves
+struct Scripted(VecDeque<i128>);
+
+impl Controller for Scripted {
+    fn joystick(&mut self, _screen: &Screen) -> Option<i128> {
+        self.0.pop_front()
+    }
+}
+
+/// Keeps the paddle under the ball
+struct AutoController;
+
+impl Controller for AutoController {
+    fn joystick(&mut self, screen: &Screen) -> Option<i128> {
+        match (screen.find(Tile::Ball), screen.find(Tile::Paddle)) {
+            (Some(ball), Some(paddle)) => Some((ball - paddle).signum() as i128),
+            _ => Some(0),
+        }
+    }
+}
+
+/// Shows the screen in the terminal and reads each move from a line of
+/// stdin: `a` for left, `d` for right, `q` to quit, and anything else for
+/// neutral
+struct KeyboardController;
+
+impl Controller for KeyboardController {
+    fn joystick(&mut self, screen: &Screen) -> Option<i128> {
+        print!("\x1b[2J\x1b[H{}[a/s/d, q to quit]: ", screen.render());
+        io::stdout().flush().ok()?;
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        match line.trim() {
+            "a" => Some(-1),
+            "d" => Some(1),
+            "q" => None,
+            _ => Some(0),
+        }
+    }
+}
+
+/// Arcade cabinet running a game that draws with output triples and pauses
+/// for the joystick whenever it reads input
+struct Arcade {
+    cabinet: IntcodeComputer,
+    screen: Screen,
+}
+
+impl Arcade {
+    /// With `free_play`, the game starts without needing quarters
+    pub fn new(program: &Program, free_play: bool) -> Arcade {
+        let mut cabinet = program.spawn(vec![], false);
+        cabinet.set_pause_on_input(true);
+        if free_play {
+            cabinet.intcodes[0] = 2;
+        }
+        Arcade {
+            cabinet,
+            screen: Screen::default(),
+        }
+    }
+
+    /// Plays until the game halts, returning `false` if the controller
+    /// stopped it first
+    pub fn run(&mut self, controller: &mut dyn Controller) -> Result<bool, String> {
+        loop {
+            let operation = self.cabinet.try_run().map_err(|err| err.to_string())?;
+            self.draw()?;
+            if operation == IntcodeOperation::Halt {
+                return Ok(true);
+            }
+            match controller.joystick(&self.screen) {
+                Some(value) => self.cabinet.inputs.push(value),
+                None => return Ok(false),
+            }
+        }
+    }
+
+    fn draw(&mut self) -> Result<(), String> {
+        let outputs: Vec<i128> = self.cabinet.outputs.drain(..).collect();
+        if !outputs.len().is_multiple_of(3) {
+            return Err(format!(
+                "{} outputs don't make up whole tiles",
+                outputs.len()
+            ));
+        }
+        for triple in outputs.chunks(3) {
+            self.screen.draw(triple[0], triple[1], triple[2])?;
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let play = args.iter().any(|arg| arg == "--play");
+    let path = match args.iter().find(|arg| *arg != "--play") {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: day-13 <input file> [--play]");
+            process::exit(1);
+        }
+    };
+    let program = match Program::load(path) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    };
+
+    let mut arcade = Arcade::new(&program, false);
+    arcade.run(&mut Scripted(VecDeque::new())).unwrap();
+    println!("Part 1 answer: {}", arcade.screen.block_count());
+
+    let mut arcade_2 = Arcade::new(&program, true);
+    if play {
+        arcade_2.run(&mut KeyboardController).unwrap();
+        println!("{}", arcade_2.screen.render());
+    } else {
+        arcade_2.run(&mut AutoController).unwrap();
+    }
+    println!("Part 2 answer: {}", arcade_2.screen.score);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Game that draws a screen, then for each move reads the joystick into
+    /// 200 and shows it as the score, halting after `moves` of them
+    fn game(tiles: &[(i128, i128, i128)], moves: usize) -> Program {
+        let mut intcodes = vec![];
+        for (x, y, tile) in tiles {
+            intcodes.extend(vec![104, *x, 104, *y, 104, *tile]);
+        }
+        for _ in 0..moves {
+            intcodes.extend(vec![3, 200, 104, -1, 104, 0, 4, 200]);
+        }
+        intcodes.push(99);
+        Program::new(intcodes)
+    }
+
+    #[test]
+    fn test_draw_screen() {
+        let program = game(&[(0, 0, 1), (1, 0, 2), (2, 0, 2), (1, 1, 3), (2, 1, 4)], 0);
+        let mut arcade = Arcade::new(&program, false);
+        assert_eq!(arcade.run(&mut Scripted(VecDeque::new())), Ok(true));
+        assert_eq!(arcade.screen.block_count(), 2);
+        assert_eq!(arcade.screen.render(), "Score: 0\n|##\n -o\n");
+    }
+
+    #[test]
+    fn test_controllers() {
+        let program = game(&[(1, 1, 3), (3, 0, 4)], 2);
+        let mut arcade = Arcade::new(&program, false);
+        let mut scripted = Scripted(vec![-1].into_iter().collect());
+        // The game pauses for a second move the script doesn't have
+        assert_eq!(arcade.run(&mut scripted), Ok(false));
+        assert_eq!(arcade.screen.score, -1);
+        assert_eq!(arcade.run(&mut AutoController), Ok(true));
+        assert_eq!(arcade.screen.score, 1);
+    }
+
+    #[test]
+    fn test_free_play() {
+        let program = game(&[(0, 0, 0)], 1);
+        let arcade = Arcade::new(&program, true);
+        assert_eq!(arcade.cabinet.intcodes[0], 2);
+        assert_eq!(program.image()[0], 104);
+    }
+}
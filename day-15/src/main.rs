@@ -0,0 +1,292 @@
+use intcode::{IntcodeComputer, IntcodeOperation, Program};
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::process;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+
+    fn to_num(self) -> i128 {
+        match self {
+            Direction::North => 1,
+            Direction::South => 2,
+            Direction::West => 3,
+            Direction::East => 4,
+        }
+    }
+
+    /// Position one tile over, with y increasing southwards
+    fn step(self, (x, y): (i64, i64)) -> (i64, i64) {
+        match self {
+            Direction::North => (x, y - 1),
+            Direction::South => (x, y + 1),
+            Direction::West => (x - 1, y),
+            Direction::East => (x + 1, y),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tile {
+    Wall,
+    Open,
+    Oxygen,
+}
+
+impl Tile {
+    fn from_reply(num: i128) -> Result<Tile, String> {
+        match num {
+            0 => Ok(Tile::Wall),
+            1 => Ok(Tile::Open),
+            2 => Ok(Tile::Oxygen),
+            n => Err(format!("invalid reply {}", n)),
+        }
+    }
+}
+
+/// Maps the area around a repair droid run by an Intcode program. The program
+/// reads a direction to move in and replies whether the droid hit a wall,
+/// moved, or moved onto the oxygen system.
+struct Explorer {
+    droid: IntcodeComputer,
+    /// Tiles found so far, relative to where the droid started
+    map: HashMap<(i64, i64), Tile>,
+    oxygen: Option<(i64, i64)>,
+}
+
+impl Explorer {
+    pub fn new(program: &Program) -> Explorer {
+        let mut map = HashMap::new();
+        map.insert((0, 0), Tile::Open);
+        // A droid asking for another move before replying pauses instead of
+        // reading its own last reply back
+        let mut droid = program.spawn(vec![], true);
+        droid.set_pause_on_input(true);
+        Explorer {
+            droid,
+            map,
+            oxygen: None,
+        }
+    }
+
+    /// Maps every reachable tile by depth-first search. Each open tile keeps a
+    /// snapshot of the droid standing on it, so backtracking resumes from an
+    /// earlier snapshot instead of walking the droid back.
+    pub fn explore(&mut self) -> Result<(), String> {
+        let mut stack = vec![((0, 0), self.droid.clone())];
+        while let Some((position, droid)) = stack.pop() {
+            for direction in Direction::ALL.iter() {
+                let next = direction.step(position);
+                if self.map.contains_key(&next) {
+                    continue;
+                }
+                let mut moved = droid.clone();
+                let tile = Explorer::send(&mut moved, *direction)?;
+                self.map.insert(next, tile);
+                if tile == Tile::Oxygen {
+                    self.oxygen = Some(next);
+                }
+                if tile != Tile::Wall {
+                    stack.push((next, moved));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn send(droid: &mut IntcodeComputer, direction: Direction) -> Result<Tile, String> {
+        droid.inputs.push(direction.to_num());
+        match droid.try_run().map_err(|err| err.to_string())? {
+            IntcodeOperation::Halt => Err("droid halted".to_string()),
+            _ => match droid.outputs.pop() {
+                Some(reply) => Tile::from_reply(reply),
+                None => Err("droid gave no reply".to_string()),
+            },
+        }
+    }
+
+    /// Fewest moves from `start` to every mapped tile that isn't a wall
+    fn distances(&self, start: (i64, i64)) -> HashMap<(i64, i64), usize> {
+        let mut distances = HashMap::new();
+        distances.insert(start, 0);
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[&position];
+            for direction in Direction::ALL.iter() {
+                let next = direction.step(position);
+                let open = self.map.get(&next).is_some_and(|tile| *tile != Tile::Wall);
+                if open && !distances.contains_key(&next) {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Fewest moves from the start to the oxygen system
+    pub fn shortest_path(&self) -> Option<usize> {
+        let oxygen = self.oxygen?;
+        self.distances((0, 0)).get(&oxygen).copied()
+    }
+
+    /// Minutes for oxygen to spread from the oxygen system to every open tile
+    pub fn fill_time(&self) -> Option<usize> {
+        let oxygen = self.oxygen?;
+        self.distances(oxygen).values().max().copied()
+    }
+
+    /// Draws the map, with `#` for walls, `.` for open tiles, `O` for the
+    /// oxygen system and `D` for where the droid started
+    pub fn render(&self) -> String {
+        let min_x = self.map.keys().map(|(x, _)| *x).min().unwrap();
+        let max_x = self.map.keys().map(|(x, _)| *x).max().unwrap();
+        let min_y = self.map.keys().map(|(_, y)| *y).min().unwrap();
+        let max_y = self.map.keys().map(|(_, y)| *y).max().unwrap();
+        (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| match self.map.get(&(x, y)) {
+                        _ if (x, y) == (0, 0) => 'D',
+                        Some(Tile::Wall) => '#',
+                        Some(Tile::Open) => '.',
+                        Some(Tile::Oxygen) => 'O',
+                        None => ' ',
+                    })
+                    .chain(std::iter::once('\n'))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+}
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: day-15 <input file>");
+            process::exit(1);
+        }
+    };
+    let program = match Program::load(&path) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    };
+
+    let mut explorer = Explorer::new(&program);
+    explorer.explore().unwrap();
+    println!("{}", explorer.render());
+    println!("Part 1 answer: {}", explorer.shortest_path().unwrap());
+    println!("Part 2 answer: {}", explorer.fill_time().unwrap());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Droid program for a maze drawn with `#` for walls, `.` for open tiles,
+    /// `O` for the oxygen system and `D` for the start. Anything off the map
+    /// is a wall. The maze is stored as a grid of replies after the code.
+    fn maze(rows: &[&str]) -> Program {
+        let width = rows.iter().map(|row| row.len()).max().unwrap() as i128 + 2;
+        let mut grid = vec![0; width as usize];
+        let mut start = 0;
+        for row in rows {
+            grid.push(0);
+            for c in row
+                .chars()
+                .chain(std::iter::repeat(' '))
+                .take(width as usize - 2)
+            {
+                if c == 'D' {
+                    start = grid.len() as i128;
+                }
+                grid.push(match c {
+                    '.' | 'D' => 1,
+                    'O' => 2,
+                    _ => 0,
+                });
+            }
+            grid.push(0);
+        }
+        grid.extend(vec![0; width as usize]);
+
+        // Memory after the code holds the direction, the droid's position in
+        // the grid, the position it's moving to, and the reply
+        let (dir, pos, next, reply, table, cells) = (30, 31, 32, 33, 34, 38);
+        let code: [&[i128]; 11] = [
+            // Read a direction and look up how far it moves in the grid
+            &[3, dir],
+            &[1001, dir, table - 1, 7],
+            &[1, 0, pos, next],
+            // Reply with the cell being moved to, and move unless it's a wall
+            &[1001, next, cells, 15],
+            &[1001, 0, 0, reply],
+            &[4, reply],
+            &[1006, reply, 0],
+            &[1001, next, 0, pos],
+            &[1105, 1, 0],
+            &[0, start, 0, 0],
+            &[-width, width, -1, 1],
+        ];
+        let mut intcodes = code.concat();
+        intcodes.extend(grid);
+        Program::new(intcodes)
+    }
+
+    const EXAMPLE: [&str; 5] = [" ##", "#..##", "#.#.D#", "#.O.#", " ###"];
+
+    #[test]
+    fn test_example() {
+        let mut explorer = Explorer::new(&maze(&EXAMPLE));
+        explorer.explore().unwrap();
+        assert_eq!(explorer.oxygen, Some((-2, 1)));
+        assert_eq!(explorer.shortest_path(), Some(3));
+        assert_eq!(explorer.fill_time(), Some(4));
+        assert_eq!(
+            explorer.render(),
+            " ##   \n#..## \n#.#.D#\n#.O.# \n ###  \n"
+        );
+        // Exploring only ever moved snapshots of the droid
+        assert_eq!(explorer.droid.steps, 0);
+    }
+
+    #[test]
+    fn test_no_oxygen() {
+        let mut explorer = Explorer::new(&maze(&["###", "#D.#", "###"]));
+        explorer.explore().unwrap();
+        assert_eq!(explorer.map.len(), 8);
+        assert_eq!(explorer.shortest_path(), None);
+        assert_eq!(explorer.fill_time(), None);
+    }
+
+    #[test]
+    fn test_halting_droid() {
+        let mut explorer = Explorer::new(&Program::new(vec![3, 10, 99]));
+        assert!(explorer.explore().is_err());
+    }
+
+    #[test]
+    fn test_silent_droid() {
+        // Reads directions forever without ever replying
+        let mut explorer = Explorer::new(&Program::new(vec![3, 10, 1105, 1, 0]));
+        assert_eq!(explorer.explore(), Err("droid gave no reply".to_string()));
+    }
+}
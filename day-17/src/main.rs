@@ -0,0 +1,364 @@
+use intcode::{AsciiAdapter, IntcodeComputer, IntcodeOperation, Program};
+use std::env;
+use std::fmt;
+use std::iter;
+use std::process;
+
+/// Longest a line of movement input can be, not counting the newline
+const MAX_LINE: usize = 20;
+const FUNCTION_NAMES: [char; 3] = ['A', 'B', 'C'];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Move {
+    Left,
+    Right,
+    Forward(usize),
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Move::Left => write!(f, "L"),
+            Move::Right => write!(f, "R"),
+            Move::Forward(steps) => write!(f, "{}", steps),
+        }
+    }
+}
+
+fn format_moves(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// View of the scaffolding from the ASCII camera
+struct Camera {
+    grid: Vec<Vec<char>>,
+}
+
+impl Camera {
+    fn parse(text: &str) -> Camera {
+        Camera {
+            grid: text
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|line| line.chars().collect())
+                .collect(),
+        }
+    }
+
+    /// Runs the camera program and reads the picture it prints
+    pub fn capture(program: &Program) -> Result<Camera, String> {
+        let mut camera = AsciiAdapter::new(program.spawn(vec![], false));
+        match camera.run().map_err(|err| err.to_string())? {
+            IntcodeOperation::Halt => Ok(Camera::parse(&camera.take_text())),
+            _ => Err("camera waited for input".to_string()),
+        }
+    }
+
+    fn get(&self, (x, y): (i64, i64)) -> Option<char> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        self.grid.get(y as usize)?.get(x as usize).copied()
+    }
+
+    /// Whether there's scaffolding at a position, including under the robot
+    fn is_scaffold(&self, position: (i64, i64)) -> bool {
+        matches!(self.get(position), Some('#' | '^' | 'v' | '<' | '>'))
+    }
+
+    /// Sum of `x * y` for every scaffold with scaffolding on all four sides
+    pub fn alignment_sum(&self) -> i64 {
+        let mut sum = 0;
+        for (y, row) in self.grid.iter().enumerate() {
+            for x in 0..row.len() {
+                let (x, y) = (x as i64, y as i64);
+                let crossing = [(x, y), (x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .iter()
+                    .all(|position| self.is_scaffold(*position));
+                if crossing {
+                    sum += x * y;
+                }
+            }
+        }
+        sum
+    }
+
+    /// Position of the robot and the direction it's facing, with y
+    /// increasing downwards
+    fn robot(&self) -> Option<((i64, i64), (i64, i64))> {
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, c) in row.iter().enumerate() {
+                let heading = match c {
+                    '^' => (0, -1),
+                    'v' => (0, 1),
+                    '<' => (-1, 0),
+                    '>' => (1, 0),
+                    _ => continue,
+                };
+                return Some(((x as i64, y as i64), heading));
+            }
+        }
+        None
+    }
+
+    /// Moves that take the robot along the scaffolding to its far end, going
+    /// straight across every intersection
+    pub fn path(&self) -> Result<Vec<Move>, String> {
+        let ((mut x, mut y), (mut dx, mut dy)) = self
+            .robot()
+            .ok_or_else(|| "no robot on camera".to_string())?;
+        let mut moves = Vec::new();
+        loop {
+            let mut steps = 0;
+            while self.is_scaffold((x + dx, y + dy)) {
+                x += dx;
+                y += dy;
+                steps += 1;
+            }
+            if steps > 0 {
+                moves.push(Move::Forward(steps));
+            }
+            if self.is_scaffold((x + dy, y - dx)) {
+                moves.push(Move::Left);
+                (dx, dy) = (dy, -dx);
+            } else if self.is_scaffold((x - dy, y + dx)) {
+                moves.push(Move::Right);
+                (dx, dy) = (-dy, dx);
+            } else {
+                return Ok(moves);
+            }
+        }
+    }
+}
+
+/// Movement input for the robot: a main routine calling up to three
+/// movement functions by index
+#[derive(Clone, Debug, PartialEq)]
+struct Routines {
+    main: Vec<usize>,
+    functions: Vec<Vec<Move>>,
+}
+
+impl Routines {
+    /// Splits a path into functions so that every line fits within
+    /// `MAX_LINE`, or `None` if it can't be done
+    pub fn compress(path: &[Move]) -> Option<Routines> {
+        let mut routines = Routines {
+            main: Vec::new(),
+            functions: Vec::new(),
+        };
+        if routines.compress_from(path) {
+            Some(routines)
+        } else {
+            None
+        }
+    }
+
+    /// Tries each known function that the rest of the path starts with, then
+    /// each new function that would fit, longest first
+    fn compress_from(&mut self, path: &[Move]) -> bool {
+        if path.is_empty() {
+            return true;
+        }
+        if self.main.len() * 2 + 1 > MAX_LINE {
+            return false;
+        }
+        for idx in 0..self.functions.len() {
+            let len = self.functions[idx].len();
+            if path.starts_with(&self.functions[idx]) {
+                self.main.push(idx);
+                if self.compress_from(&path[len..]) {
+                    return true;
+                }
+                self.main.pop();
+            }
+        }
+        if self.functions.len() == FUNCTION_NAMES.len() {
+            return false;
+        }
+        for len in (1..=path.len()).rev() {
+            if format_moves(&path[..len]).len() > MAX_LINE {
+                continue;
+            }
+            self.main.push(self.functions.len());
+            self.functions.push(path[..len].to_vec());
+            if self.compress_from(&path[len..]) {
+                return true;
+            }
+            self.functions.pop();
+            self.main.pop();
+        }
+        false
+    }
+
+    /// Input lines for the main routine and then each of the three functions,
+    /// with unused functions left empty
+    pub fn lines(&self) -> Vec<String> {
+        let main = self
+            .main
+            .iter()
+            .map(|idx| FUNCTION_NAMES[*idx].to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        iter::once(main)
+            .chain((0..FUNCTION_NAMES.len()).map(|idx| {
+                self.functions
+                    .get(idx)
+                    .map(|function| format_moves(function))
+                    .unwrap_or_default()
+            }))
+            .collect()
+    }
+}
+
+/// Answers each of the robot's prompts with the routines, turns down the
+/// video feed, and returns the dust it reports once it halts
+fn collect_dust(robot: IntcodeComputer, routines: &Routines) -> Result<i128, String> {
+    let mut robot = AsciiAdapter::new(robot);
+    for line in routines
+        .lines()
+        .iter()
+        .map(String::as_str)
+        .chain(iter::once("n"))
+    {
+        match robot.run().map_err(|err| err.to_string())? {
+            IntcodeOperation::Halt => return Err(format!("robot halted before {:?}", line)),
+            _ => robot.send_line(line),
+        }
+    }
+    robot.run().map_err(|err| err.to_string())?;
+    robot
+        .take_values()
+        .pop()
+        .ok_or_else(|| format!("no dust reported: {}", robot.take_text()))
+}
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: day-17 <input file>");
+            process::exit(1);
+        }
+    };
+    let program = match Program::load(&path) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    };
+
+    let camera = Camera::capture(&program).unwrap();
+    println!("Part 1 answer: {}", camera.alignment_sum());
+
+    let routines = Routines::compress(&camera.path().unwrap()).unwrap();
+    let mut robot = program.spawn(vec![], false);
+    // Wake the robot up so it moves instead of only showing the camera
+    robot.intcodes[0] = 2;
+    println!("Part 2 answer: {}", collect_dust(robot, &routines).unwrap());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INTERSECTIONS: &str = "..#..........
+..#..........
+#######...###
+#.#...#...#.#
+#############
+..#...#...#..
+..#####...^..
+";
+
+    const PATH: &str = "#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#.#.#.#....
+....##.##.#....
+....#...#......
+....#####......
+";
+
+    /// The moves routines expand to
+    fn expand(routines: &Routines) -> Vec<Move> {
+        routines
+            .main
+            .iter()
+            .flat_map(|idx| routines.functions[*idx].iter().copied())
+            .collect()
+    }
+
+    /// Program that prints some text and halts
+    fn printer(text: &str) -> Program {
+        let mut intcodes: Vec<i128> = text.bytes().flat_map(|b| vec![104, b as i128]).collect();
+        intcodes.push(99);
+        Program::new(intcodes)
+    }
+
+    #[test]
+    fn test_alignment_sum() {
+        let camera = Camera::capture(&printer(INTERSECTIONS)).unwrap();
+        assert_eq!(camera.grid.len(), 7);
+        assert_eq!(camera.alignment_sum(), 76);
+    }
+
+    #[test]
+    fn test_path_and_compress() {
+        let camera = Camera::parse(PATH);
+        let path = camera.path().unwrap();
+        assert_eq!(
+            format_moves(&path),
+            "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2"
+        );
+
+        let routines = Routines::compress(&path).unwrap();
+        assert_eq!(expand(&routines), path);
+        let lines = routines.lines();
+        assert_eq!(lines.len(), 4);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE));
+    }
+
+    #[test]
+    fn test_compress_too_long() {
+        // No move repeats, and three functions only fit 21 of them
+        let path: Vec<Move> = (10..40).map(Move::Forward).collect();
+        assert_eq!(Routines::compress(&path), None);
+    }
+
+    #[test]
+    fn test_collect_dust() {
+        // Stands in for the robot by reading a fixed number of characters and
+        // reporting their sum as the dust
+        let routines = Routines {
+            main: vec![0, 1, 0],
+            functions: vec![vec![Move::Left, Move::Forward(10)], vec![Move::Right]],
+        };
+        let input: String = routines
+            .lines()
+            .iter()
+            .map(|line| line.clone() + "\n")
+            .collect();
+        let input = input + "n\n";
+        let mut intcodes = vec![
+            3, 16, 1, 16, 17, 17, 1001, 18, -1, 18, 1005, 18, 0, 4, 17, 99, 0, 0,
+        ];
+        intcodes.push(input.len() as i128);
+        let robot = IntcodeComputer::new(intcodes, vec![], false);
+        let dust = input.bytes().map(i128::from).sum::<i128>();
+        assert_eq!(collect_dust(robot, &routines), Ok(dust));
+    }
+}
@@ -1,112 +1,74 @@
-use std::fs;
-use std::io;
+use intcode::{InputRecorder, Program, Recording, Replayer, StdinInput};
+use std::env;
+use std::fs::File;
+use std::sync::{Arc, Mutex};
 
-fn parse_instruction(instruction: isize) -> (isize, isize, isize, isize) {
-    let opcode = instruction % 100;
-    let mode_1 = instruction % 1000 / 100;
-    let mode_2 = instruction % 10000 / 1000;
-    let mode_3 = instruction % 100000 / 10000;
-    (opcode, mode_1, mode_2, mode_3)
-}
-
-fn get_arg_val(intcode_slice: &mut [isize], arg: isize, mode: isize) -> isize {
-    match mode {
-        0 => {
-            if (arg as usize) < intcode_slice.len() {
-                return intcode_slice[arg as usize];
-            }
-            0
-        }
-        1 => arg,
-        _ => 0,
-    }
-}
-
-fn handle_instruction(
-    intcode_slice: &mut [isize],
-    index: usize,
-    opcode: isize,
-    mode_1: isize,
-    mode_2: isize,
-    mode_3: isize,
-) -> (bool, isize, isize, usize) {
-    let arg1 = intcode_slice[index + 1];
-    let arg2 = intcode_slice[index + 2];
-    let arg3 = intcode_slice[index + 3];
-    let arg1_val = get_arg_val(intcode_slice, arg1, mode_1);
-    let arg2_val = get_arg_val(intcode_slice, arg2, mode_2);
+fn main() {
+    let program = Program::load("input.txt").unwrap();
+    let mut computer = program.spawn(vec![], false);
 
-    match opcode {
-        1 => (true, arg3, arg1_val + arg2_val, index + 4),
-        2 => (true, arg3, arg1_val * arg2_val, index + 4),
-        3 => {
-            // Get user input
-            let mut input = String::new();
-            println!("Please enter input:");
-            io::stdin()
-                .read_line(&mut input)
-                .expect("error: unable to read user input");
-            (
-                true,
-                arg1,
-                input.trim().parse::<isize>().unwrap_or(0),
-                index + 2,
-            )
-        }
-        4 => {
-            println!("{:?}", arg1_val);
-            (false, 0, 0, index + 2)
+    // Optionally record the session's inputs to a file or replay them from one
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut recorder = None;
+    let mut replayer = None;
+    match args
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<&str>>()
+        .as_slice()
+    {
+        ["--record", path] => {
+            let source = InputRecorder::new(StdinInput)
+                .with_log(File::create(path).unwrap())
+                .unwrap();
+            let source = Arc::new(Mutex::new(source));
+            computer.set_input_source(source.clone());
+            recorder = Some(source);
         }
-        5 => {
-            let place = if arg1_val != 0 {
-                arg2_val as usize
-            } else {
-                index + 3
-            };
-            (false, 0, 0, place)
+        ["--replay", path] => {
+            let source = Arc::new(Mutex::new(Replayer::new(Recording::load(path).unwrap())));
+            computer.set_input_source(source.clone());
+            // Stop where the recording runs out rather than making up inputs
+            computer.set_pause_on_input(true);
+            replayer = Some(source);
         }
-        6 => {
-            let place = if arg1_val == 0 {
-                arg2_val as usize
-            } else {
-                index + 3
-            };
-            (false, 0, 0, place)
+        [] => computer.set_input_source(Arc::new(Mutex::new(StdinInput))),
+        _ => {
+            eprintln!("usage: day-5 [--record <file> | --replay <file>]");
+            return;
         }
-        7 => (true, arg3, (arg1_val < arg2_val) as isize, index + 4),
-        8 => (true, arg3, (arg1_val == arg2_val) as isize, index + 4),
-        _ => (false, 0, 0, index + 1),
     }
-}
 
-fn process_intcodes(intcode_slice: &mut [isize]) -> isize {
-    let mut n = 0;
-    while n < intcode_slice.len() {
-        let (opcode, m_1, m_2, m_3) = parse_instruction(intcode_slice[n]);
-        match opcode {
-            (1..=8) => {
-                let (is_op, idx, val, place) =
-                    handle_instruction(intcode_slice, n, opcode, m_1, m_2, m_3);
-                if is_op {
-                    intcode_slice[idx as usize] = val;
-                }
-                n = place;
-            }
-            99 => break,
-            n => {
-                println!("Alt code: {}", n);
+    for output in computer.outputs_iter() {
+        match output {
+            Ok(output) => println!("{:?}", output),
+            Err(err) => {
+                eprintln!("Program faulted: {}", err);
                 break;
             }
         }
     }
-    intcode_slice[0]
-}
 
-fn main() {
-    let contents = fs::read_to_string("input.txt").unwrap();
-    let mut intcode_slice: Vec<isize> = contents
-        .split(',')
-        .map(|s| s.parse::<isize>().unwrap_or(0))
-        .collect();
-    process_intcodes(intcode_slice.as_mut_slice());
+    if let Some(recorder) = recorder {
+        if let Some(err) = recorder.lock().unwrap().log_error() {
+            eprintln!("Recording is incomplete: {}", err);
+        }
+    }
+    if let Some(replayer) = replayer {
+        let replayer = replayer.lock().unwrap();
+        if let Some((recorded, actual)) = replayer.divergence {
+            eprintln!(
+                "Replay diverged: input recorded at step {} was read at step {}",
+                recorded, actual
+            );
+        }
+        if replayer.exhausted {
+            eprintln!("Replay ran out of recorded inputs");
+        } else if !replayer.is_finished() {
+            eprintln!(
+                "Replay left {} recorded inputs unused",
+                replayer.remaining()
+            );
+        }
+    }
 }
@@ -0,0 +1,247 @@
+use crate::computer::{IntcodeComputer, IntcodeOperation};
+use crate::program::Program;
+use std::sync::Mutex;
+use std::thread;
+
+/// How the output of the last amplifier is handled
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AmplifierWiring {
+    /// The last amplifier's first output is the final signal
+    Serial,
+    /// The last amplifier feeds back into the first until every amplifier halts
+    Feedback,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PhaseObjective {
+    Maximize,
+    Minimize,
+    Target(i128),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhaseResult {
+    pub phases: Vec<i128>,
+    pub signal: i128,
+}
+
+/// Runs a chain of amplifiers, one per phase setting, starting with an input
+/// signal of 0 and returning the last signal sent out of the final amplifier.
+/// Returns `None` if an amplifier faults or the chain runs more than
+/// `step_limit` instructions in total.
+pub fn run_amplifiers(
+    program: &Program,
+    phases: &[i128],
+    wiring: AmplifierWiring,
+    step_limit: usize,
+) -> Option<i128> {
+    let mut amps: Vec<IntcodeComputer> = phases
+        .iter()
+        .map(|phase| program.spawn(vec![*phase], false))
+        .collect();
+    let mut signal = 0;
+    let mut steps = 0;
+    loop {
+        for amp in amps.iter_mut() {
+            amp.inputs.push(signal);
+            loop {
+                if steps == step_limit {
+                    return None;
+                }
+                steps += 1;
+                match amp.try_step().ok()? {
+                    Some(IntcodeOperation::Output) => {
+                        signal = amp.outputs.pop()?;
+                        break;
+                    }
+                    Some(IntcodeOperation::Halt) => return Some(signal),
+                    _ => {}
+                }
+            }
+        }
+        if wiring == AmplifierWiring::Serial {
+            return Some(signal);
+        }
+    }
+}
+
+/// Searches every ordering of distinct phase settings across a number of
+/// amplifiers for the one best matching an objective
+pub struct PhaseSearch {
+    program: Program,
+    amplifiers: usize,
+    phases: Vec<i128>,
+    wiring: AmplifierWiring,
+    threads: usize,
+    step_limit: usize,
+}
+
+impl PhaseSearch {
+    pub fn new(
+        program: &Program,
+        amplifiers: usize,
+        phases: Vec<i128>,
+        wiring: AmplifierWiring,
+    ) -> PhaseSearch {
+        PhaseSearch {
+            program: program.clone(),
+            amplifiers,
+            phases,
+            wiring,
+            threads: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            step_limit: 1_000_000,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> PhaseSearch {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Most instructions a chain of amplifiers runs before its phase sequence
+    /// is treated as not matching
+    pub fn with_step_limit(mut self, step_limit: usize) -> PhaseSearch {
+        self.step_limit = step_limit;
+        self
+    }
+
+    /// Returns the best phase sequence and its signal. Sequences that fault
+    /// or run past the step limit are skipped. Ties go to the sequence
+    /// that comes first in lexicographic order of phase positions.
+    pub fn run(&self, objective: PhaseObjective) -> Option<PhaseResult> {
+        let sequences = permutations(&self.phases, self.amplifiers);
+        let signals = Mutex::new(Vec::with_capacity(sequences.len()));
+        thread::scope(|s| {
+            for offset in 0..self.threads {
+                let sequences = &sequences;
+                let signals = &signals;
+                s.spawn(move || {
+                    let found: Vec<(usize, i128)> = (offset..sequences.len())
+                        .step_by(self.threads)
+                        .filter_map(|n| {
+                            let phases = &sequences[n];
+                            run_amplifiers(&self.program, phases, self.wiring, self.step_limit)
+                                .map(|signal| (n, signal))
+                        })
+                        .collect();
+                    signals.lock().unwrap().extend(found);
+                });
+            }
+        });
+        let mut signals = signals.into_inner().unwrap();
+        signals.sort_unstable();
+
+        let best = match objective {
+            PhaseObjective::Maximize => signals
+                .into_iter()
+                .min_by_key(|(n, signal)| (std::cmp::Reverse(*signal), *n)),
+            PhaseObjective::Minimize => signals.into_iter().min_by_key(|(n, signal)| (*signal, *n)),
+            PhaseObjective::Target(target) => {
+                signals.into_iter().find(|(_, signal)| *signal == target)
+            }
+        };
+        best.map(|(n, signal)| PhaseResult {
+            phases: sequences[n].clone(),
+            signal,
+        })
+    }
+}
+
+/// Every ordered selection of `len` distinct values, in lexicographic order of
+/// their positions in `values`
+fn permutations(values: &[i128], len: usize) -> Vec<Vec<i128>> {
+    if len == 0 {
+        return vec![vec![]];
+    }
+    let mut result = Vec::new();
+    for (idx, value) in values.iter().enumerate() {
+        let mut rest = values.to_vec();
+        rest.remove(idx);
+        for mut tail in permutations(&rest, len - 1) {
+            tail.insert(0, *value);
+            result.push(tail);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serial_sample() {
+        let program = Program::new(vec![
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ]);
+        let search = PhaseSearch::new(&program, 5, vec![0, 1, 2, 3, 4], AmplifierWiring::Serial);
+        assert_eq!(
+            search.run(PhaseObjective::Maximize),
+            Some(PhaseResult {
+                phases: vec![4, 3, 2, 1, 0],
+                signal: 43210
+            })
+        );
+        assert_eq!(
+            search.run(PhaseObjective::Minimize),
+            Some(PhaseResult {
+                phases: vec![0, 1, 2, 3, 4],
+                signal: 1234
+            })
+        );
+        assert_eq!(
+            search.run(PhaseObjective::Target(21430)).map(|r| r.phases),
+            Some(vec![2, 1, 4, 3, 0])
+        );
+    }
+
+    #[test]
+    fn test_feedback_sample() {
+        let program = Program::new(vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ]);
+        let search = PhaseSearch::new(&program, 5, vec![5, 6, 7, 8, 9], AmplifierWiring::Feedback);
+        assert_eq!(
+            search.run(PhaseObjective::Maximize),
+            Some(PhaseResult {
+                phases: vec![9, 8, 7, 6, 5],
+                signal: 139629729
+            })
+        );
+    }
+
+    #[test]
+    fn test_faults_and_loops_never_match() {
+        // Faults on phase 0, loops forever on phase 1 and echoes phase 2
+        let program = Program::new(vec![
+            3, 30, 3, 31, 1008, 30, 0, 32, 1005, 32, -1, 1008, 30, 1, 32, 1005, 32, 11, 4, 31, 99,
+        ]);
+        let search = PhaseSearch::new(&program, 1, vec![0, 1, 2], AmplifierWiring::Serial)
+            .with_step_limit(1000);
+        assert_eq!(
+            run_amplifiers(&program, &[0], AmplifierWiring::Serial, 1000),
+            None
+        );
+        assert_eq!(
+            run_amplifiers(&program, &[1], AmplifierWiring::Serial, 1000),
+            None
+        );
+        assert_eq!(
+            search.run(PhaseObjective::Minimize),
+            Some(PhaseResult {
+                phases: vec![2],
+                signal: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_partial_permutations() {
+        assert_eq!(permutations(&[1, 2, 3], 2).len(), 6);
+        assert_eq!(permutations(&[1, 2, 3], 2)[1], vec![1, 3]);
+        assert!(permutations(&[1, 2], 3).is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::computer::{IntcodeComputer, IntcodeOperation};
+use crate::error::IntcodeError;
+use std::mem;
+
+/// Talks to a machine in ASCII text. Lines are sent as character codes, and
+/// outputs are read back as text, except for values outside ASCII, such as a
+/// final answer, which are kept separately.
+pub struct AsciiAdapter {
+    computer: IntcodeComputer,
+    text: String,
+    values: Vec<i128>,
+}
+
+impl AsciiAdapter {
+    /// Wraps a machine, making it pause whenever it waits for input
+    pub fn new(mut computer: IntcodeComputer) -> AsciiAdapter {
+        computer.set_pause_on_input(true);
+        AsciiAdapter {
+            computer,
+            text: String::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Queues a line of input, followed by a newline
+    pub fn send_line(&mut self, line: &str) {
+        self.computer
+            .inputs
+            .extend(line.bytes().chain(Some(b'\n')).map(i128::from));
+    }
+
+    /// Runs until the machine halts or waits for more input, collecting its
+    /// output
+    pub fn run(&mut self) -> Result<IntcodeOperation, IntcodeError> {
+        let operation = self.computer.try_run()?;
+        for value in self.computer.outputs.drain(..) {
+            match value {
+                0..=127 => self.text.push(value as u8 as char),
+                _ => self.values.push(value),
+            }
+        }
+        Ok(operation)
+    }
+
+    /// Text printed since it was last taken
+    pub fn take_text(&mut self) -> String {
+        mem::take(&mut self.text)
+    }
+
+    /// Outputs outside ASCII since they were last taken
+    pub fn take_values(&mut self) -> Vec<i128> {
+        mem::take(&mut self.values)
+    }
+
+    pub fn computer(&self) -> &IntcodeComputer {
+        &self.computer
+    }
+
+    pub fn into_computer(self) -> IntcodeComputer {
+        self.computer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_text_and_values() {
+        // Prints "Hi", then reads a character and prints the next one along
+        // with 1000
+        let program = vec![
+            104, 72, 104, 105, 104, 10, 3, 19, 1001, 19, 1, 19, 4, 19, 104, 1000, 99, 0, 0, 0,
+        ];
+        let mut adapter = AsciiAdapter::new(IntcodeComputer::new(program, vec![], false));
+        assert_eq!(adapter.run(), Ok(IntcodeOperation::Input));
+        assert_eq!(adapter.take_text(), "Hi\n");
+        assert_eq!(adapter.take_values(), vec![]);
+
+        adapter.send_line("a");
+        assert_eq!(adapter.run(), Ok(IntcodeOperation::Halt));
+        assert_eq!(adapter.take_text(), "b");
+        assert_eq!(adapter.take_values(), vec![1000]);
+        assert_eq!(adapter.computer().inputs, vec![10]);
+    }
+}
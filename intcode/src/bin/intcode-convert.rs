@@ -0,0 +1,48 @@
+use intcode::{format_symbols, parse_symbols, Program, ProgramContainer};
+use std::env;
+use std::fs;
+use std::process;
+
+const USAGE: &str = "usage: intcode-convert pack <program.txt> <program.icb> [symbols.txt]
+       intcode-convert unpack <program.icb> <program.txt> [symbols.txt]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<&str>>()
+        .as_slice()
+    {
+        ["pack", input, output, rest @ ..] if rest.len() <= 1 => pack(input, output, rest.first()),
+        ["unpack", input, output, rest @ ..] if rest.len() <= 1 => {
+            unpack(input, output, rest.first())
+        }
+        _ => Err(USAGE.to_string()),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn pack(input: &str, output: &str, symbols: Option<&&str>) -> Result<(), String> {
+    let program = Program::load(input).map_err(|err| format!("{}: {}", input, err))?;
+    let mut container = ProgramContainer::new(program);
+    if let Some(path) = symbols {
+        let contents = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+        container.symbols = parse_symbols(&contents).map_err(|err| format!("{}: {}", path, err))?;
+    }
+    fs::write(output, container.to_bytes()).map_err(|err| format!("{}: {}", output, err))
+}
+
+fn unpack(input: &str, output: &str, symbols: Option<&&str>) -> Result<(), String> {
+    let container = ProgramContainer::load(input).map_err(|err| format!("{}: {}", input, err))?;
+    fs::write(output, format!("{}\n", container.program))
+        .map_err(|err| format!("{}: {}", output, err))?;
+    if let Some(path) = symbols {
+        fs::write(path, format_symbols(&container.symbols))
+            .map_err(|err| format!("{}: {}", path, err))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use intcode::DapServer;
+use std::io;
+use std::process;
+
+fn main() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut server = DapServer::new(stdin.lock(), stdout.lock());
+    if let Err(err) = server.run() {
+        eprintln!("Debug adapter failed: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,33 @@
+use intcode::{parse_symbols, Decompiler, Program};
+use std::env;
+use std::fs;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("usage: intcode-decompile <program.txt> [symbols]");
+        process::exit(1);
+    }
+    let program = match Program::load(&args[0]) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}: {}", args[0], err);
+            process::exit(1);
+        }
+    };
+    let mut decompiler = Decompiler::new(&program);
+    if let Some(path) = args.get(1) {
+        let symbols = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| parse_symbols(&contents).map_err(|err| err.to_string()));
+        match symbols {
+            Ok(symbols) => decompiler = decompiler.with_symbols(&symbols),
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                process::exit(1);
+            }
+        }
+    }
+    print!("{}", decompiler.render());
+}
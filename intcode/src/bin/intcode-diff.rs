@@ -0,0 +1,70 @@
+use intcode::{Program, DEFAULT_MEMORY_LIMIT};
+use std::env;
+use std::process;
+
+fn usage() -> ! {
+    eprintln!("usage: intcode-diff <program.txt> <snapshot.txt>");
+    eprintln!(
+        "       intcode-diff <program.txt> --run [--set address=value]... [--input value]..."
+    );
+    process::exit(1);
+}
+
+fn load(path: &str) -> Program {
+    match Program::load(path) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 2 {
+        usage();
+    }
+    let program = load(&args[0]);
+    if args[1] != "--run" {
+        if args.len() != 2 {
+            usage();
+        }
+        let snapshot = load(&args[1]).spawn(vec![], false);
+        print!("{}", program.diff(&snapshot));
+        return;
+    }
+
+    // Runs the program to completion with the patches and inputs, then shows
+    // everything that changed from the image
+    let mut computer = program.spawn(vec![], false);
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| usage());
+        match option.as_str() {
+            "--set" => {
+                let parts: Vec<&str> = value.splitn(2, '=').collect();
+                match (
+                    parts.first().and_then(|a| a.parse::<usize>().ok()),
+                    parts.get(1).and_then(|v| v.parse::<i128>().ok()),
+                ) {
+                    (Some(address), Some(value)) if address < DEFAULT_MEMORY_LIMIT => {
+                        computer.intcodes.extend(address + 1);
+                        computer.intcodes[address] = value;
+                    }
+                    _ => usage(),
+                }
+            }
+            "--input" => match value.parse() {
+                Ok(value) => computer.inputs.push(value),
+                Err(_) => usage(),
+            },
+            _ => usage(),
+        }
+    }
+    if let Err(err) = computer.try_run() {
+        eprintln!("{}: {}", args[0], err);
+        process::exit(1);
+    }
+    print!("{}", program.diff(&computer));
+}
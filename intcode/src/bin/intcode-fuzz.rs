@@ -0,0 +1,47 @@
+use intcode::Fuzzer;
+use std::env;
+use std::panic;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn usage() -> ! {
+    eprintln!("usage: intcode-fuzz [--seed n] [--iterations n] [--corpus dir]");
+    process::exit(1);
+}
+
+fn main() {
+    let mut seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or(1);
+    let mut iterations = 100_000;
+    let mut corpus = "tests/fuzz_corpus".to_string();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| usage());
+        match option.as_str() {
+            "--seed" => seed = value.parse().unwrap_or_else(|_| usage()),
+            "--iterations" => iterations = value.parse().unwrap_or_else(|_| usage()),
+            "--corpus" => corpus = value.clone(),
+            _ => usage(),
+        }
+    }
+
+    // Crashes are reported below, so skip printing every caught panic
+    panic::set_hook(Box::new(|_| {}));
+    println!("Fuzzing {} cases with seed {}", iterations, seed);
+    let crashes = Fuzzer::new(seed).run(iterations);
+    let _ = panic::take_hook();
+    for crash in crashes.iter() {
+        match crash.case.save(&corpus, &crash.message) {
+            Ok(path) => println!("{}: {}", path.display(), crash.message),
+            Err(err) => eprintln!("{}: {}", corpus, err),
+        }
+    }
+    println!("{} distinct crashes", crashes.len());
+    if !crashes.is_empty() {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,37 @@
+use intcode::{GdbStub, Program, StdinInput};
+use std::env;
+use std::net::TcpListener;
+use std::process;
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("usage: intcode-gdb <program.txt> [port]");
+        process::exit(1);
+    }
+    let program = match Program::load(&args[0]) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}: {}", args[0], err);
+            process::exit(1);
+        }
+    };
+    let port = args.get(1).map(|port| port.as_str()).unwrap_or("1234");
+    let listener = match TcpListener::bind(format!("127.0.0.1:{}", port)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Unable to listen on port {}: {}", port, err);
+            process::exit(1);
+        }
+    };
+
+    let mut computer = program.spawn(vec![], false);
+    computer.set_input_source(Arc::new(Mutex::new(StdinInput)));
+    let mut stub = GdbStub::new(computer);
+    println!("Waiting for a debugger on 127.0.0.1:{}", port);
+    if let Err(err) = stub.serve(&listener) {
+        eprintln!("Connection failed: {}", err);
+        process::exit(1);
+    }
+}
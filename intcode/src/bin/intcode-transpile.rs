@@ -0,0 +1,23 @@
+use intcode::{transpile, Program};
+use std::env;
+use std::fs;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() != 2 {
+        eprintln!("usage: intcode-transpile <program.txt> <module.rs>");
+        process::exit(1);
+    }
+    let program = match Program::load(&args[0]) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("{}: {}", args[0], err);
+            process::exit(1);
+        }
+    };
+    if let Err(err) = fs::write(&args[1], transpile(&program)) {
+        eprintln!("{}: {}", args[1], err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,381 @@
+use crate::computer::IntcodeComputer;
+use crate::program::Program;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"ICPB";
+const VERSION: u8 = 1;
+const FLAG_ENTRY: u8 = 0b01;
+const FLAG_SYMBOLS: u8 = 0b10;
+
+#[derive(Debug)]
+pub enum BinaryError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnsupportedWidth(u8),
+    /// Input ended in the middle of a field
+    Truncated,
+    /// A varint that runs past 128 bits, or a count or address that doesn't
+    /// fit in `usize`
+    BadVarint,
+    /// A word doesn't fit in the width from the header
+    WordOutOfRange(usize),
+    InvalidSymbol(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BinaryError::BadMagic => write!(f, "not an Intcode binary program"),
+            BinaryError::UnsupportedVersion(v) => write!(f, "unsupported version {}", v),
+            BinaryError::UnsupportedWidth(w) => write!(f, "unsupported word width {}", w),
+            BinaryError::Truncated => write!(f, "unexpected end of program"),
+            BinaryError::BadVarint => write!(f, "varint too long for its field"),
+            BinaryError::WordOutOfRange(idx) => {
+                write!(f, "word {} doesn't fit in the header's word width", idx)
+            }
+            BinaryError::InvalidSymbol(line) => write!(f, "invalid symbol {:?}", line),
+            BinaryError::Io(err) => write!(f, "unable to read program: {}", err),
+        }
+    }
+}
+
+impl Error for BinaryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BinaryError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BinaryError {
+    fn from(err: io::Error) -> BinaryError {
+        BinaryError::Io(err)
+    }
+}
+
+/// Program image along with the metadata stored in the binary format:
+///
+/// | field       | encoding                                         |
+/// |-------------|--------------------------------------------------|
+/// | magic       | `ICPB`                                           |
+/// | version     | `u8`, currently 1                                |
+/// | word width  | `u8` bit width of the widest word: 8 through 128 |
+/// | flags       | `u8`, bit 0 for entry and bit 1 for symbols      |
+/// | entry       | varint, if flagged                               |
+/// | symbols     | varint count, then address and name per symbol   |
+/// | words       | varint count, then zigzag varint per word        |
+///
+/// Names are a varint length followed by UTF-8 bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgramContainer {
+    pub program: Program,
+    /// Address to start executing from instead of 0
+    pub entry: Option<usize>,
+    pub symbols: BTreeMap<String, usize>,
+}
+
+impl ProgramContainer {
+    pub fn new(program: Program) -> ProgramContainer {
+        ProgramContainer {
+            program,
+            entry: None,
+            symbols: BTreeMap::new(),
+        }
+    }
+
+    /// Smallest supported width in bits that can hold every word
+    pub fn word_width(&self) -> u8 {
+        let widest = self
+            .program
+            .image()
+            .iter()
+            .map(|word| 129 - (if *word < 0 { !*word } else { *word }).leading_zeros())
+            .max()
+            .unwrap_or(0);
+        [8, 16, 32, 64, 128]
+            .iter()
+            .copied()
+            .find(|width| *width as u32 >= widest)
+            .unwrap_or(128)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.push(self.word_width());
+        let mut flags = 0;
+        if self.entry.is_some() {
+            flags |= FLAG_ENTRY;
+        }
+        if !self.symbols.is_empty() {
+            flags |= FLAG_SYMBOLS;
+        }
+        bytes.push(flags);
+        if let Some(entry) = self.entry {
+            write_varint(&mut bytes, entry as u128);
+        }
+        if !self.symbols.is_empty() {
+            write_varint(&mut bytes, self.symbols.len() as u128);
+            for (name, address) in self.symbols.iter() {
+                write_varint(&mut bytes, *address as u128);
+                write_varint(&mut bytes, name.len() as u128);
+                bytes.extend_from_slice(name.as_bytes());
+            }
+        }
+        write_varint(&mut bytes, self.program.len() as u128);
+        for word in self.program.image().iter() {
+            write_varint(&mut bytes, zigzag(*word));
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ProgramContainer, BinaryError> {
+        let mut reader = ByteReader { bytes, pos: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(BinaryError::BadMagic);
+        }
+        match reader.byte()? {
+            VERSION => {}
+            v => return Err(BinaryError::UnsupportedVersion(v)),
+        }
+        let width = reader.byte()?;
+        if ![8, 16, 32, 64, 128].contains(&width) {
+            return Err(BinaryError::UnsupportedWidth(width));
+        }
+        let flags = reader.byte()?;
+
+        let entry = if flags & FLAG_ENTRY != 0 {
+            Some(reader.usize_varint()?)
+        } else {
+            None
+        };
+        let mut symbols = BTreeMap::new();
+        if flags & FLAG_SYMBOLS != 0 {
+            for _ in 0..reader.usize_varint()? {
+                let address = reader.usize_varint()?;
+                let len = reader.usize_varint()?;
+                let name = String::from_utf8(reader.take(len)?.to_vec())
+                    .map_err(|err| BinaryError::InvalidSymbol(err.to_string()))?;
+                symbols.insert(name, address);
+            }
+        }
+
+        let count = reader.usize_varint()?;
+        let mut intcodes = Vec::with_capacity(count.min(bytes.len()));
+        for idx in 0..count {
+            let word = unzigzag(reader.varint()?);
+            if width < 128 && (word < -(1 << (width - 1)) || word >= 1 << (width - 1)) {
+                return Err(BinaryError::WordOutOfRange(idx));
+            }
+            intcodes.push(word);
+        }
+        Ok(ProgramContainer {
+            program: Program::new(intcodes),
+            entry,
+            symbols,
+        })
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<ProgramContainer, BinaryError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        ProgramContainer::from_bytes(&bytes)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ProgramContainer, BinaryError> {
+        ProgramContainer::from_bytes(&fs::read(path)?)
+    }
+
+    /// Spawns a machine from the program, starting at the entry address
+    pub fn spawn(&self, inputs: Vec<i128>, pause_on_output: bool) -> IntcodeComputer {
+        let mut computer = self.program.spawn(inputs, pause_on_output);
+        computer.index = self.entry.unwrap_or(0);
+        computer
+    }
+}
+
+impl IntcodeComputer {
+    /// Loads a machine directly from a binary program container
+    pub fn from_binary(
+        bytes: &[u8],
+        inputs: Vec<i128>,
+        pause_on_output: bool,
+    ) -> Result<IntcodeComputer, BinaryError> {
+        Ok(ProgramContainer::from_bytes(bytes)?.spawn(inputs, pause_on_output))
+    }
+}
+
+/// Parses a symbol file with a name and an address on each line. Blank lines
+/// and lines starting with `#` are skipped.
+pub fn parse_symbols(contents: &str) -> Result<BTreeMap<String, usize>, BinaryError> {
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                [name, address] => address
+                    .parse::<usize>()
+                    .map(|address| (name.to_string(), address))
+                    .map_err(|_| BinaryError::InvalidSymbol(line.to_string())),
+                _ => Err(BinaryError::InvalidSymbol(line.to_string())),
+            }
+        })
+        .collect()
+}
+
+pub fn format_symbols(symbols: &BTreeMap<String, usize>) -> String {
+    symbols
+        .iter()
+        .map(|(name, address)| format!("{} {}\n", name, address))
+        .collect()
+}
+
+fn zigzag(value: i128) -> u128 {
+    ((value << 1) ^ (value >> 127)) as u128
+}
+
+fn unzigzag(value: u128) -> i128 {
+    ((value >> 1) as i128) ^ -((value & 1) as i128)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u128) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BinaryError> {
+        let end = self.pos.checked_add(len).ok_or(BinaryError::Truncated)?;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(BinaryError::Truncated)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, BinaryError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u128, BinaryError> {
+        let mut value: u128 = 0;
+        for shift in (0..128).step_by(7) {
+            let byte = self.byte()?;
+            // The last byte only has room for the top 2 bits
+            if shift == 126 && byte & 0x7c != 0 {
+                return Err(BinaryError::BadVarint);
+            }
+            value |= ((byte & 0x7f) as u128) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(BinaryError::BadVarint)
+    }
+
+    fn usize_varint(&mut self) -> Result<usize, BinaryError> {
+        usize::try_from(self.varint()?).map_err(|_| BinaryError::BadVarint)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut container =
+            ProgramContainer::new(Program::new(vec![109, -1, 204, 1, 99, 1125899906842624]));
+        container.entry = Some(2);
+        container.symbols.insert("start".to_string(), 2);
+        let bytes = container.to_bytes();
+        assert_eq!(&bytes[..7], &[b'I', b'C', b'P', b'B', 1, 64, 0b11]);
+        assert_eq!(ProgramContainer::from_bytes(&bytes).unwrap(), container);
+
+        let computer = IntcodeComputer::from_binary(&bytes, vec![], false).unwrap();
+        assert_eq!(computer.index, 2);
+    }
+
+    #[test]
+    fn test_extreme_words() {
+        let words = vec![0, -1, 1, i128::MAX, i128::MIN, 127, -128, 128];
+        let container = ProgramContainer::new(Program::new(words.clone()));
+        assert_eq!(container.word_width(), 128);
+        let decoded = ProgramContainer::from_bytes(&container.to_bytes()).unwrap();
+        assert_eq!(decoded.program.image().to_vec(), words);
+        assert_eq!(
+            ProgramContainer::new(Program::new(vec![127, -128])).word_width(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_invalid_binary() {
+        assert!(matches!(
+            ProgramContainer::from_bytes(b"1,2,3"),
+            Err(BinaryError::BadMagic)
+        ));
+        let bytes = ProgramContainer::new(Program::new(vec![1, 2, 3])).to_bytes();
+        assert!(matches!(
+            ProgramContainer::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BinaryError::Truncated)
+        ));
+        // Claims 8 bit words but holds a 16 bit word
+        let mut bytes = ProgramContainer::new(Program::new(vec![1000])).to_bytes();
+        bytes[5] = 8;
+        assert!(matches!(
+            ProgramContainer::from_bytes(&bytes),
+            Err(BinaryError::WordOutOfRange(0))
+        ));
+
+        // Word counts with bits past 128, or too large for a usize
+        let header = ProgramContainer::new(Program::new(vec![])).to_bytes();
+        let header = &header[..header.len() - 1];
+        let mut overlong = header.to_vec();
+        overlong.extend(vec![0x80; 18]);
+        overlong.push(0x04);
+        let mut huge = header.to_vec();
+        write_varint(&mut huge, 1 << 64);
+        for bytes in [overlong, huge].iter() {
+            assert!(matches!(
+                ProgramContainer::from_bytes(bytes),
+                Err(BinaryError::BadVarint)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_symbol_file() {
+        let symbols = parse_symbols("# comment\nmain 0\n\nloop 12\n").unwrap();
+        assert_eq!(symbols.get("loop"), Some(&12));
+        assert_eq!(parse_symbols(&format_symbols(&symbols)).unwrap(), symbols);
+        assert!(parse_symbols("main").is_err());
+    }
+}
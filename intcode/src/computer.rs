@@ -0,0 +1,652 @@
+use crate::error::IntcodeError;
+use crate::events::EventLog;
+use crate::extension::OpcodeRegistry;
+use crate::memory::Memory;
+use crate::observer::ObserverSlot;
+use crate::replay::InputSlot;
+use std::fmt::Debug;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntcodeOperation {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equal,
+    RelativeBaseOffset,
+    Halt,
+}
+
+impl IntcodeOperation {
+    pub fn from_num(num: i128) -> IntcodeOperation {
+        IntcodeOperation::try_from_num(num)
+            .unwrap_or_else(|| panic!("Invalid opcode {} supplied", num))
+    }
+
+    pub fn try_from_num(num: i128) -> Option<IntcodeOperation> {
+        match num {
+            1 => Some(IntcodeOperation::Add),
+            2 => Some(IntcodeOperation::Multiply),
+            3 => Some(IntcodeOperation::Input),
+            4 => Some(IntcodeOperation::Output),
+            5 => Some(IntcodeOperation::JumpIfTrue),
+            6 => Some(IntcodeOperation::JumpIfFalse),
+            7 => Some(IntcodeOperation::LessThan),
+            8 => Some(IntcodeOperation::Equal),
+            9 => Some(IntcodeOperation::RelativeBaseOffset),
+            99 => Some(IntcodeOperation::Halt),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntcodeMode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl IntcodeMode {
+    pub fn from_num(num: i128) -> IntcodeMode {
+        IntcodeMode::try_from_num(num).unwrap_or_else(|| panic!("Invalid number {} supplied", num))
+    }
+
+    pub fn try_from_num(num: i128) -> Option<IntcodeMode> {
+        match num {
+            0 => Some(IntcodeMode::Position),
+            1 => Some(IntcodeMode::Immediate),
+            2 => Some(IntcodeMode::Relative),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IntcodeInstruction {
+    pub operation: IntcodeOperation,
+    pub mode_1: IntcodeMode,
+    pub mode_2: IntcodeMode,
+    pub mode_3: IntcodeMode,
+}
+
+impl IntcodeInstruction {
+    pub fn new(
+        operation: IntcodeOperation,
+        mode_1: IntcodeMode,
+        mode_2: IntcodeMode,
+        mode_3: IntcodeMode,
+    ) -> IntcodeInstruction {
+        IntcodeInstruction {
+            operation,
+            mode_1,
+            mode_2,
+            mode_3,
+        }
+    }
+
+    pub fn from_num(num: i128) -> IntcodeInstruction {
+        IntcodeInstruction::try_from_num(num)
+            .unwrap_or_else(|| panic!("Invalid instruction {} supplied", num))
+    }
+
+    /// Decodes an instruction, or returns `None` for an unknown opcode or
+    /// mode, including any mode digits past the third
+    pub fn try_from_num(num: i128) -> Option<IntcodeInstruction> {
+        if !(0..100000).contains(&num) {
+            return None;
+        }
+        Some(IntcodeInstruction::new(
+            IntcodeOperation::try_from_num(num % 100)?,
+            IntcodeMode::try_from_num(num % 1000 / 100)?,
+            IntcodeMode::try_from_num(num % 10000 / 1000)?,
+            IntcodeMode::try_from_num(num % 100000 / 10000)?,
+        ))
+    }
+}
+
+pub(crate) fn instruction_len(operation: IntcodeOperation) -> usize {
+    match operation {
+        IntcodeOperation::Add
+        | IntcodeOperation::Multiply
+        | IntcodeOperation::LessThan
+        | IntcodeOperation::Equal => 4,
+        IntcodeOperation::JumpIfTrue | IntcodeOperation::JumpIfFalse => 3,
+        IntcodeOperation::Input
+        | IntcodeOperation::Output
+        | IntcodeOperation::RelativeBaseOffset => 2,
+        IntcodeOperation::Halt => 1,
+    }
+}
+
+/// Faults on a negative address or one at or past `limit`, for parameter
+/// `param` of the instruction at `index`
+pub(crate) fn check_address(
+    index: usize,
+    param: usize,
+    address: i128,
+    limit: usize,
+) -> Result<(), IntcodeError> {
+    if address < 0 {
+        Err(IntcodeError::NegativeAddress {
+            index,
+            param,
+            address,
+        })
+    } else if address >= limit as i128 {
+        Err(IntcodeError::OutOfBounds {
+            index,
+            param,
+            address,
+            limit,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// How a machine handles addresses outside of its memory
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AddressingPolicy {
+    /// Negative position mode reads give 0, memory grows to fit any address
+    /// up to the memory limit, and other negative addresses fault
+    #[default]
+    Lenient,
+    /// Faults on any negative address or any address at or past `limit`.
+    /// Memory still grows with zeros up to the limit.
+    Strict { limit: usize },
+}
+
+/// Memory limit in words for new machines, far more than any real program
+/// needs, so a stray address faults instead of using up the host's memory
+pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 24;
+
+#[derive(Clone, Debug)]
+pub struct IntcodeComputer {
+    pub intcodes: Memory,
+    // TODO: Might need to refactor
+    pub inputs: Vec<i128>,
+    pub outputs: Vec<i128>,
+    pub index: usize,
+    pub relative_base: i128,
+    /// Number of instructions executed so far
+    pub steps: usize,
+    pause_on_output: bool,
+    pause_on_input: bool,
+    addressing: AddressingPolicy,
+    memory_limit: usize,
+    pub(crate) opcodes: OpcodeRegistry,
+    pub(crate) observer: ObserverSlot,
+    pub(crate) input_source: InputSlot,
+    /// Value taken from the input source while checking whether to pause
+    pub(crate) pulled_input: Option<i128>,
+    pub(crate) output_events: Option<EventLog>,
+}
+
+impl IntcodeComputer {
+    pub fn new(intcodes: Vec<i128>, inputs: Vec<i128>, pause_on_output: bool) -> IntcodeComputer {
+        IntcodeComputer::with_memory(Memory::from(intcodes), inputs, pause_on_output)
+    }
+
+    pub(crate) fn with_memory(
+        intcodes: Memory,
+        inputs: Vec<i128>,
+        pause_on_output: bool,
+    ) -> IntcodeComputer {
+        IntcodeComputer {
+            intcodes,
+            inputs,
+            outputs: Vec::new(),
+            index: 0,
+            relative_base: 0,
+            steps: 0,
+            pause_on_output,
+            pause_on_input: false,
+            addressing: AddressingPolicy::default(),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            opcodes: OpcodeRegistry::default(),
+            observer: ObserverSlot::default(),
+            input_source: InputSlot::default(),
+            pulled_input: None,
+            output_events: None,
+        }
+    }
+
+    pub fn set_addressing(&mut self, addressing: AddressingPolicy) {
+        self.addressing = addressing;
+    }
+
+    /// Makes an `Input` with nothing to read pause the machine before running
+    /// it, returning `Input` from `run`, instead of taking back the last
+    /// output
+    pub fn set_pause_on_input(&mut self, pause_on_input: bool) {
+        self.pause_on_input = pause_on_input;
+    }
+
+    /// Caps how far memory can grow under either addressing policy, faulting
+    /// on any access at or past `limit`. Defaults to `DEFAULT_MEMORY_LIMIT`.
+    pub fn set_memory_limit(&mut self, limit: usize) {
+        self.memory_limit = limit;
+    }
+
+    pub(crate) fn limit(&self) -> usize {
+        match self.addressing {
+            AddressingPolicy::Lenient => self.memory_limit,
+            AddressingPolicy::Strict { limit } => limit.min(self.memory_limit),
+        }
+    }
+
+    /// Runs until the program halts, or pauses on output or input. Panics on
+    /// a fault.
+    pub fn run(&mut self) -> IntcodeOperation {
+        self.try_run().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Runs the same as `run`, but returns faults instead of panicking. The
+    /// machine is left at the faulting instruction.
+    pub fn try_run(&mut self) -> Result<IntcodeOperation, IntcodeError> {
+        loop {
+            let index = self.index;
+            match self.try_step()? {
+                Some(IntcodeOperation::Halt) => return Ok(IntcodeOperation::Halt),
+                Some(IntcodeOperation::Output) if self.pause_on_output => {
+                    return Ok(IntcodeOperation::Output)
+                }
+                Some(IntcodeOperation::Input) if self.index == index => {
+                    return Ok(IntcodeOperation::Input)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Executes a single instruction and returns its operation, or `None` for
+    /// a custom opcode. Stepping at a `Halt`, or at an `Input` paused for
+    /// input, leaves the machine where it is.
+    pub fn step(&mut self) -> Option<IntcodeOperation> {
+        self.try_step().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_step(&mut self) -> Result<Option<IntcodeOperation>, IntcodeError> {
+        // Running off the end reads a 0, which halts
+        self.check_address(0, self.index as i128, self.limit())?;
+        self.extend_intcodes(self.index + 1);
+        let num = self.intcodes[self.index];
+        if !self.opcodes.is_empty() {
+            if let Some(opcode) = self.opcodes.get(num % 100).cloned() {
+                self.handle_custom(&opcode)?;
+                self.steps += 1;
+                return Ok(None);
+            }
+        }
+        for param in 1..=3 {
+            self.mode(num, param)?;
+        }
+        let instruction = match IntcodeInstruction::try_from_num(num) {
+            Some(instruction) => instruction,
+            // Zeroed memory halts, as it does past the end of the program
+            None if num == 0 => IntcodeInstruction::from_num(99),
+            None => {
+                return Err(IntcodeError::InvalidInstruction {
+                    index: self.index,
+                    instruction: num,
+                })
+            }
+        };
+        if instruction.operation == IntcodeOperation::Input && self.waiting_for_input() {
+            return Ok(Some(IntcodeOperation::Input));
+        }
+        let index = self.index;
+        self.observer
+            .notify(|o| o.before_instruction(index, &instruction));
+        if let IntcodeOperation::Halt = instruction.operation {
+            self.observer.notify(|o| o.on_halt(index));
+            return Ok(Some(IntcodeOperation::Halt));
+        }
+        self.handle_instruction(instruction)?;
+        self.steps += 1;
+        self.observer
+            .notify(|o| o.after_instruction(index, &instruction));
+        Ok(Some(instruction.operation))
+    }
+
+    /// Whether an `Input` should pause, pulling from the input source first
+    /// so that a paused machine has really run out of input
+    fn waiting_for_input(&mut self) -> bool {
+        if !self.pause_on_input || !self.inputs.is_empty() || self.pulled_input.is_some() {
+            return false;
+        }
+        self.pulled_input = self.input_source.next_input(self.steps);
+        self.pulled_input.is_none()
+    }
+
+    /// Mode of parameter `param` in the instruction `num`
+    pub(crate) fn mode(&self, num: i128, param: usize) -> Result<IntcodeMode, IntcodeError> {
+        let digit = num / 10_i128.pow(param as u32 + 1) % 10;
+        IntcodeMode::try_from_num(digit).ok_or(IntcodeError::InvalidMode {
+            index: self.index,
+            param,
+            mode: digit,
+        })
+    }
+
+    fn overflow(&self) -> IntcodeError {
+        IntcodeError::Overflow { index: self.index }
+    }
+
+    fn check_address(&self, param: usize, address: i128, limit: usize) -> Result<(), IntcodeError> {
+        check_address(self.index, param, address, limit)
+    }
+
+    /// Value of parameter `param` of the current instruction, or the address
+    /// it refers to for a destination, following the addressing policy
+    pub(crate) fn resolve(
+        &mut self,
+        param: usize,
+        mode: IntcodeMode,
+        dest: bool,
+    ) -> Result<i128, IntcodeError> {
+        let arg = *self.intcodes.get(self.index + param).unwrap_or(&0);
+        // A destination in immediate mode is written to like position mode
+        let address = match mode {
+            IntcodeMode::Immediate if !dest => return Ok(arg),
+            IntcodeMode::Immediate | IntcodeMode::Position => arg,
+            IntcodeMode::Relative => self
+                .relative_base
+                .checked_add(arg)
+                .ok_or_else(|| self.overflow())?,
+        };
+        let lenient = self.addressing == AddressingPolicy::Lenient;
+        if lenient && mode == IntcodeMode::Position && !dest && address < 0 {
+            return Ok(0);
+        }
+        self.check_address(param, address, self.limit())?;
+        if dest {
+            // Lenient addressing grows memory to a relative destination
+            // before the write does
+            if lenient && mode == IntcodeMode::Relative {
+                self.extend_intcodes(address as usize + 1);
+            }
+            Ok(address)
+        } else {
+            self.extend_intcodes(address as usize + 1);
+            Ok(self.intcodes[address as usize])
+        }
+    }
+
+    fn handle_instruction(&mut self, instruction: IntcodeInstruction) -> Result<(), IntcodeError> {
+        // Lenient addressing resolves every parameter for every instruction,
+        // which can grow memory, so only strict addressing skips unused ones
+        let params = instruction_len(instruction.operation) - 1;
+        let used = match self.addressing {
+            AddressingPolicy::Lenient => 3,
+            AddressingPolicy::Strict { .. } => params,
+        };
+        let input = instruction.operation == IntcodeOperation::Input;
+        let mut values = [0; 3];
+        let modes = [instruction.mode_1, instruction.mode_2, instruction.mode_3];
+        for param in 1..=used {
+            let dest = param == 3 || (input && param == 1 && used == 1);
+            match self.resolve(param, modes[param - 1], dest) {
+                Ok(value) => values[param - 1] = value,
+                // Parameters an operation doesn't use can't fault
+                Err(_) if param > params => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let [arg_1_val, arg_2_val, arg_3_val] = values;
+
+        match instruction.operation {
+            IntcodeOperation::Add => {
+                let value = arg_1_val
+                    .checked_add(arg_2_val)
+                    .ok_or_else(|| self.overflow())?;
+                self.write(arg_3_val as usize, value);
+                self.index += 4;
+            }
+            IntcodeOperation::Multiply => {
+                let value = arg_1_val
+                    .checked_mul(arg_2_val)
+                    .ok_or_else(|| self.overflow())?;
+                self.write(arg_3_val as usize, value);
+                self.index += 4;
+            }
+            IntcodeOperation::Input => {
+                let arg_1_dest = self.resolve(1, instruction.mode_1, true)?;
+                let value = if let Some(value) = self.pulled_input.take() {
+                    value
+                } else if !self.inputs.is_empty() {
+                    let value = self.inputs.remove(0);
+                    self.input_source.on_queued_input(self.steps, value);
+                    value
+                } else if let Some(value) = self.input_source.next_input(self.steps) {
+                    value
+                } else {
+                    self.outputs.pop().unwrap_or(0)
+                };
+                self.observer.notify(|o| o.on_input(value));
+                if let Some(log) = &mut self.output_events {
+                    log.on_input(value);
+                }
+                self.write(arg_1_dest as usize, value);
+                self.index += 2;
+            }
+            IntcodeOperation::Output => {
+                self.observer.notify(|o| o.on_output(arg_1_val));
+                if let Some(log) = &mut self.output_events {
+                    log.on_output(arg_1_val, self.steps, self.index);
+                }
+                self.outputs.push(arg_1_val);
+                self.index += 2;
+            }
+            IntcodeOperation::JumpIfTrue => {
+                self.index = match arg_1_val {
+                    0 => self.index + 3,
+                    _ => self.jump_target(arg_2_val)?,
+                };
+            }
+            IntcodeOperation::JumpIfFalse => {
+                self.index = match arg_1_val {
+                    0 => self.jump_target(arg_2_val)?,
+                    _ => self.index + 3,
+                };
+            }
+            IntcodeOperation::LessThan => {
+                self.write(arg_3_val as usize, (arg_1_val < arg_2_val) as i128);
+                self.index += 4;
+            }
+            IntcodeOperation::Equal => {
+                self.write(arg_3_val as usize, (arg_1_val == arg_2_val) as i128);
+                self.index += 4;
+            }
+            IntcodeOperation::RelativeBaseOffset => {
+                let old = self.relative_base;
+                self.relative_base = old.checked_add(arg_1_val).ok_or_else(|| self.overflow())?;
+                let new = self.relative_base;
+                self.observer
+                    .notify(|o| o.on_relative_base_change(old, new));
+                self.index += 2;
+            }
+            IntcodeOperation::Halt => {}
+        }
+        Ok(())
+    }
+
+    fn jump_target(&self, target: i128) -> Result<usize, IntcodeError> {
+        self.check_address(2, target, self.limit())?;
+        Ok(target as usize)
+    }
+
+    pub(crate) fn write(&mut self, address: usize, value: i128) {
+        self.extend_intcodes(address + 1);
+        let old = self.intcodes[address];
+        self.intcodes[address] = value;
+        self.observer
+            .notify(|o| o.on_memory_write(address, old, value));
+    }
+
+    fn extend_intcodes(&mut self, len: usize) {
+        self.intcodes.extend(len);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::Program;
+
+    #[test]
+    fn test_decode() {
+        assert!(IntcodeInstruction::try_from_num(301).is_none());
+        assert!(IntcodeInstruction::try_from_num(1002).is_some());
+        assert!(IntcodeInstruction::try_from_num(42).is_none());
+        assert!(IntcodeInstruction::try_from_num(100001).is_none());
+        assert_eq!(IntcodeOperation::try_from_num(0), None);
+    }
+
+    #[test]
+    fn test_invalid_instruction() {
+        let mut computer = Program::new(vec![104, 7, 42, 99]).spawn(vec![], false);
+        let err = computer.try_run().unwrap_err();
+        assert_eq!(
+            err,
+            IntcodeError::InvalidInstruction {
+                index: 2,
+                instruction: 42,
+            }
+        );
+        assert_eq!(err.to_string(), "invalid instruction 42 at 2");
+        assert_eq!(computer.outputs, vec![7]);
+    }
+
+    #[test]
+    fn test_negative_position_read() {
+        let program = Program::new(vec![1, -1, 0, 0, 99]);
+        let mut lenient = program.spawn(vec![], false);
+        assert_eq!(lenient.run(), IntcodeOperation::Halt);
+        assert_eq!(lenient.intcodes[0], 1);
+
+        let mut strict = program.spawn(vec![], false);
+        strict.set_addressing(AddressingPolicy::Strict { limit: 100 });
+        let err = strict.try_run().unwrap_err();
+        assert_eq!(
+            err,
+            IntcodeError::NegativeAddress {
+                index: 0,
+                param: 1,
+                address: -1,
+            }
+        );
+        assert_eq!(strict.index, 0);
+        assert_eq!(strict.intcodes[0], 1);
+    }
+
+    #[test]
+    fn test_relative_past_limit() {
+        // Reads within the limit grow memory, then writes past it
+        let program = Program::new(vec![109, 10, 1201, 5, 1, 8, 21101, 1, 1, 6, 99]);
+        let mut computer = program.spawn(vec![], false);
+        computer.set_addressing(AddressingPolicy::Strict { limit: 16 });
+        let err = computer.try_run().unwrap_err();
+        assert_eq!(
+            err,
+            IntcodeError::OutOfBounds {
+                index: 6,
+                param: 3,
+                address: 16,
+                limit: 16,
+            }
+        );
+        assert_eq!(computer.intcodes.len(), 16);
+        assert_eq!(computer.intcodes[8], 1);
+        assert_eq!(
+            err.to_string(),
+            "address 16 past the limit of 16 in parameter 3 of the instruction at 6"
+        );
+    }
+
+    #[test]
+    fn test_lenient_faults() {
+        let mut computer = Program::new(vec![482]).spawn(vec![], false);
+        assert_eq!(
+            computer.try_step(),
+            Err(IntcodeError::InvalidMode {
+                index: 0,
+                param: 1,
+                mode: 4,
+            })
+        );
+
+        let mut computer = Program::new(vec![1102, i128::MAX, 2, 0]).spawn(vec![], false);
+        assert_eq!(
+            computer.try_step(),
+            Err(IntcodeError::Overflow { index: 0 })
+        );
+
+        // Runs off the end, which reads as a halt
+        let mut computer = Program::new(vec![104, 7]).spawn(vec![], false);
+        assert_eq!(computer.try_run(), Ok(IntcodeOperation::Halt));
+        assert_eq!(computer.outputs, vec![7]);
+    }
+
+    #[test]
+    fn test_jump_and_fetch() {
+        let program = Program::new(vec![1105, 1, -3, 99]);
+        let mut computer = program.spawn(vec![], false);
+        computer.set_addressing(AddressingPolicy::Strict { limit: 10 });
+        assert_eq!(
+            computer.try_step(),
+            Err(IntcodeError::NegativeAddress {
+                index: 0,
+                param: 2,
+                address: -3,
+            })
+        );
+
+        // Runs off the end of memory
+        let program = Program::new(vec![1101, 1, 1, 3]);
+        let mut computer = program.spawn(vec![], false);
+        computer.set_addressing(AddressingPolicy::Strict { limit: 4 });
+        assert_eq!(computer.try_step(), Ok(Some(IntcodeOperation::Add)));
+        assert_eq!(
+            computer.try_step(),
+            Err(IntcodeError::OutOfBounds {
+                index: 4,
+                param: 0,
+                address: 4,
+                limit: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_default_memory_limit() {
+        // A far jump or write faults instead of growing memory to fit it
+        let far = 1 << 50;
+        let mut computer = Program::new(vec![1105, 1, far]).spawn(vec![], false);
+        assert_eq!(
+            computer.try_run(),
+            Err(IntcodeError::OutOfBounds {
+                index: 0,
+                param: 2,
+                address: far,
+                limit: DEFAULT_MEMORY_LIMIT,
+            })
+        );
+
+        let mut computer = Program::new(vec![1101, 1, 1, far, 99]).spawn(vec![], false);
+        assert_eq!(
+            computer.try_run(),
+            Err(IntcodeError::OutOfBounds {
+                index: 0,
+                param: 3,
+                address: far,
+                limit: DEFAULT_MEMORY_LIMIT,
+            })
+        );
+        assert_eq!(computer.intcodes.len(), 5);
+    }
+}
@@ -0,0 +1,760 @@
+use crate::binary::{parse_symbols, ProgramContainer};
+use crate::computer::{AddressingPolicy, IntcodeComputer, IntcodeOperation};
+use crate::error::IntcodeError;
+use crate::memory::WORD_BYTES;
+use crate::program::Program;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+const THREAD_ID: i64 = 1;
+/// Variable references for the scopes and lists in the variables view
+const MACHINE_REF: i64 = 1;
+const INPUTS_REF: i64 = 2;
+const OUTPUTS_REF: i64 = 3;
+const MEMORY_REF: i64 = 4;
+/// Most bytes or words of memory sent in one response
+const MAX_COUNT: usize = 1 << 16;
+/// Largest message a client can send
+const MAX_MESSAGE: usize = 1 << 24;
+
+/// A launched program and everything the client has set on it
+struct Session {
+    computer: IntcodeComputer,
+    symbols: BTreeMap<String, usize>,
+    instruction_breakpoints: BTreeSet<usize>,
+    function_breakpoints: BTreeSet<usize>,
+    stop_on_entry: bool,
+    /// Outputs already sent to the client
+    reported: usize,
+    halted: bool,
+    /// Addressing fault the machine is stopped at
+    fault: Option<IntcodeError>,
+}
+
+impl Session {
+    fn is_breakpoint(&self, address: usize) -> bool {
+        self.instruction_breakpoints.contains(&address)
+            || self.function_breakpoints.contains(&address)
+    }
+
+    /// Resolves a label or an address
+    fn address(&self, reference: &str) -> Option<usize> {
+        match self.symbols.get(reference) {
+            Some(address) => Some(*address),
+            None => reference.trim().parse().ok(),
+        }
+    }
+
+    /// Closest label at or before an address, with the offset from it
+    fn location(&self, address: usize) -> String {
+        let label = self
+            .symbols
+            .iter()
+            .filter(|(_, start)| **start <= address)
+            .max_by_key(|(_, start)| **start);
+        match label {
+            Some((name, start)) if *start == address => name.clone(),
+            Some((name, start)) => format!("{}+{}", name, address - start),
+            None => format!("ip {}", address),
+        }
+    }
+}
+
+/// Debug Adapter Protocol server driving a single machine, normally over
+/// stdin and stdout.
+///
+/// `launch` takes the `program` path, either comma-separated text or a binary
+/// container, along with optional `inputs`, `stopOnEntry` and `symbols`, a
+/// path to a symbol file. Without one, a `.sym` file next to the program is
+/// used if it exists. An `addressLimit` turns on strict addressing, and faults
+/// stop with an exception. Breakpoints are set as instruction breakpoints, or as
+/// function breakpoints, by address or label. Memory is read and written as
+/// 8 bytes per word, the same as `GdbStub`, with the memory reference being a
+/// word address.
+///
+/// Requests are handled one at a time, so `continue` runs until a breakpoint,
+/// fault or halt before anything else is read, and `pause` isn't supported.
+pub struct DapServer<R: BufRead, W: Write> {
+    reader: R,
+    writer: W,
+    seq: i64,
+    session: Option<Session>,
+}
+
+impl<R: BufRead, W: Write> DapServer<R, W> {
+    pub fn new(reader: R, writer: W) -> DapServer<R, W> {
+        DapServer {
+            reader,
+            writer,
+            seq: 0,
+            session: None,
+        }
+    }
+
+    /// Handles requests until the client disconnects or closes the stream
+    pub fn run(&mut self) -> io::Result<()> {
+        while let Some(request) = self.read_message()? {
+            let command = request["command"].as_str().unwrap_or("").to_string();
+            let result = self.handle(&command, &request["arguments"]);
+            let (success, body) = match &result {
+                Ok(body) => (true, body.clone()),
+                Err(message) => (false, json!({ "error": { "id": 1, "format": message } })),
+            };
+            let mut response = json!({
+                "type": "response",
+                "request_seq": request["seq"],
+                "command": command,
+                "success": success,
+                "body": body,
+            });
+            if let Err(message) = result {
+                response["message"] = json!(message);
+            }
+            self.send(response)?;
+            self.after(&command, success)?;
+            if command == "disconnect" {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, command: &str, args: &Value) -> Result<Value, String> {
+        match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsFunctionBreakpoints": true,
+                "supportsInstructionBreakpoints": true,
+                "supportsReadMemoryRequest": true,
+                "supportsWriteMemoryRequest": true,
+            })),
+            "launch" => {
+                self.session = Some(launch(args)?);
+                Ok(json!({}))
+            }
+            "disconnect" | "configurationDone" => Ok(json!({})),
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "intcode" }] })),
+            "setBreakpoints" => {
+                // Source lines don't mean anything for Intcode
+                let count = args["breakpoints"].as_array().map_or(0, |b| b.len());
+                let breakpoints = vec![json!({ "verified": false }); count];
+                Ok(json!({ "breakpoints": breakpoints }))
+            }
+            "setInstructionBreakpoints" | "setFunctionBreakpoints" => {
+                let session = self.session()?;
+                let key = match command {
+                    "setInstructionBreakpoints" => "instructionReference",
+                    _ => "name",
+                };
+                let mut addresses = BTreeSet::new();
+                let mut breakpoints = Vec::new();
+                for breakpoint in args["breakpoints"].as_array().unwrap_or(&Vec::new()) {
+                    let reference = breakpoint[key].as_str().unwrap_or("");
+                    let offset = breakpoint["offset"].as_i64().unwrap_or(0);
+                    let address = session
+                        .address(reference)
+                        .and_then(|address| (address as i64).checked_add(offset))
+                        .filter(|address| *address >= 0);
+                    match address {
+                        Some(address) => {
+                            addresses.insert(address as usize);
+                            breakpoints.push(json!({
+                                "verified": true,
+                                "instructionReference": address.to_string(),
+                            }));
+                        }
+                        None => breakpoints.push(json!({
+                            "verified": false,
+                            "message": format!("unknown address or label {:?}", reference),
+                        })),
+                    }
+                }
+                if command == "setInstructionBreakpoints" {
+                    session.instruction_breakpoints = addresses;
+                } else {
+                    session.function_breakpoints = addresses;
+                }
+                Ok(json!({ "breakpoints": breakpoints }))
+            }
+            "continue" => {
+                self.resume(false)?;
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "next" | "stepIn" => {
+                self.resume(true)?;
+                Ok(json!({}))
+            }
+            "stackTrace" => {
+                let session = self.session()?;
+                let index = session.computer.index;
+                Ok(json!({
+                    "stackFrames": [{
+                        "id": 1,
+                        "name": session.location(index),
+                        "line": 0,
+                        "column": 0,
+                        "instructionPointerReference": index.to_string(),
+                    }],
+                    "totalFrames": 1,
+                }))
+            }
+            "scopes" => {
+                let len = self.session()?.computer.intcodes.len();
+                Ok(json!({ "scopes": [
+                    { "name": "Machine", "variablesReference": MACHINE_REF, "expensive": false },
+                    {
+                        "name": "Memory",
+                        "variablesReference": MEMORY_REF,
+                        "indexedVariables": len,
+                        "expensive": true,
+                    },
+                ]}))
+            }
+            "variables" => {
+                let session = self.session()?;
+                let reference = args["variablesReference"].as_i64().unwrap_or(0);
+                Ok(json!({ "variables": variables(session, reference, args) }))
+            }
+            "readMemory" => {
+                let session = self.session()?;
+                let start = memory_address(args)?;
+                let count = args["count"].as_u64().unwrap_or(0);
+                let count = (count as usize).min(MAX_COUNT);
+                let limit = session.computer.limit();
+                let bytes = session.computer.intcodes.read_bytes(start, count, limit);
+                let bytes = bytes.ok_or_else(|| "memory range is out of bounds".to_string())?;
+                Ok(json!({
+                    "address": format!("0x{:x}", start),
+                    "data": base64_encode(&bytes),
+                }))
+            }
+            "writeMemory" => {
+                let start = memory_address(args)?;
+                let data = args["data"].as_str().and_then(base64_decode);
+                let data = data.ok_or_else(|| "invalid base64 data".to_string())?;
+                let computer = &mut self.session()?.computer;
+                let limit = computer.limit();
+                computer
+                    .intcodes
+                    .write_bytes(start, &data, limit)
+                    .ok_or_else(|| "memory range is out of bounds".to_string())?;
+                Ok(json!({ "bytesWritten": data.len() }))
+            }
+            _ => Err(format!("unsupported request {:?}", command)),
+        }
+    }
+
+    /// Events that follow a response
+    fn after(&mut self, command: &str, success: bool) -> io::Result<()> {
+        if !success {
+            return Ok(());
+        }
+        match command {
+            "initialize" => self.event("initialized", json!({})),
+            "configurationDone" => match &self.session {
+                Some(session) if session.stop_on_entry => self.stopped("entry"),
+                Some(_) => {
+                    if let Err(message) = self.resume(false) {
+                        self.output("stderr", &format!("{}\n", message))?;
+                    }
+                    self.report()
+                }
+                None => Ok(()),
+            },
+            "continue" | "next" | "stepIn" => self.report(),
+            _ => Ok(()),
+        }
+    }
+
+    fn session(&mut self) -> Result<&mut Session, String> {
+        self.session
+            .as_mut()
+            .ok_or_else(|| "no program has been launched".to_string())
+    }
+
+    /// Runs a single instruction, or until a breakpoint or halt
+    fn resume(&mut self, single: bool) -> Result<(), String> {
+        let session = self.session()?;
+        if session.halted {
+            return Err("the program has halted".to_string());
+        }
+        session.fault = None;
+        loop {
+            match session.computer.try_step() {
+                Ok(Some(IntcodeOperation::Halt)) => {
+                    session.halted = true;
+                    return Ok(());
+                }
+                Err(fault) => {
+                    session.fault = Some(fault);
+                    return Ok(());
+                }
+                _ => {}
+            }
+            // An input with nothing queued takes back the last output, which
+            // may have been sent already
+            session.reported = session.reported.min(session.computer.outputs.len());
+            if single || session.is_breakpoint(session.computer.index) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Sends new outputs and why the program stopped after it ran
+    fn report(&mut self) -> io::Result<()> {
+        let (outputs, halted, fault, at_breakpoint) = match &mut self.session {
+            Some(session) => {
+                let start = session.reported.min(session.computer.outputs.len());
+                let outputs = session.computer.outputs[start..].to_vec();
+                session.reported = session.computer.outputs.len();
+                let index = session.computer.index;
+                let fault = session.fault.clone();
+                (outputs, session.halted, fault, session.is_breakpoint(index))
+            }
+            None => return Ok(()),
+        };
+        for value in outputs {
+            self.output("stdout", &format!("{}\n", value))?;
+        }
+        if halted {
+            self.event("exited", json!({ "exitCode": 0 }))?;
+            self.event("terminated", json!({}))
+        } else if let Some(fault) = fault {
+            self.event(
+                "stopped",
+                json!({
+                    "reason": "exception",
+                    "description": fault.to_string(),
+                    "threadId": THREAD_ID,
+                    "allThreadsStopped": true,
+                }),
+            )
+        } else if at_breakpoint {
+            self.stopped("breakpoint")
+        } else {
+            self.stopped("step")
+        }
+    }
+
+    fn stopped(&mut self, reason: &str) -> io::Result<()> {
+        self.event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        )
+    }
+
+    fn output(&mut self, category: &str, output: &str) -> io::Result<()> {
+        self.event("output", json!({ "category": category, "output": output }))
+    }
+
+    fn event(&mut self, event: &str, body: Value) -> io::Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        let content = message.to_string();
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )?;
+        self.writer.flush()
+    }
+
+    fn read_message(&mut self) -> io::Result<Option<Value>> {
+        let mut len = None;
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length:") {
+                len = value.trim().parse::<usize>().ok();
+            }
+        }
+        let len = len.ok_or_else(|| invalid("missing Content-Length header"))?;
+        if len > MAX_MESSAGE {
+            return Err(invalid("message is too long"));
+        }
+        let mut content = vec![0; len];
+        self.reader.read_exact(&mut content)?;
+        serde_json::from_slice(&content)
+            .map(Some)
+            .map_err(|err| invalid(&err.to_string()))
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn launch(args: &Value) -> Result<Session, String> {
+    let path = args["program"]
+        .as_str()
+        .ok_or_else(|| "launch needs a program path".to_string())?;
+    let bytes = fs::read(path).map_err(|err| format!("{}: {}", path, err))?;
+    let container = match ProgramContainer::from_bytes(&bytes) {
+        Ok(container) => container,
+        Err(_) => {
+            let program: Program = String::from_utf8_lossy(&bytes)
+                .parse()
+                .map_err(|err| format!("{}: {}", path, err))?;
+            ProgramContainer::new(program)
+        }
+    };
+
+    let mut symbols = container.symbols.clone();
+    let default_symbols = Path::new(path).with_extension("sym");
+    let symbol_path = match args["symbols"].as_str() {
+        Some(symbol_path) => Some(Path::new(symbol_path).to_path_buf()),
+        None if default_symbols.exists() => Some(default_symbols),
+        None => None,
+    };
+    if let Some(symbol_path) = symbol_path {
+        let contents = fs::read_to_string(&symbol_path)
+            .map_err(|err| format!("{}: {}", symbol_path.display(), err))?;
+        symbols.extend(parse_symbols(&contents).map_err(|err| err.to_string())?);
+    }
+
+    let inputs = match &args["inputs"] {
+        Value::Null => Vec::new(),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| value.as_i64().map(|v| v as i128))
+            .collect::<Option<Vec<i128>>>()
+            .ok_or_else(|| "inputs must be integers".to_string())?,
+        _ => return Err("inputs must be an array".to_string()),
+    };
+    let mut computer = container.spawn(inputs, false);
+    if let Some(limit) = args["addressLimit"].as_u64() {
+        computer.set_addressing(AddressingPolicy::Strict {
+            limit: limit as usize,
+        });
+    }
+    Ok(Session {
+        computer,
+        symbols,
+        instruction_breakpoints: BTreeSet::new(),
+        function_breakpoints: BTreeSet::new(),
+        stop_on_entry: args["stopOnEntry"].as_bool().unwrap_or(false),
+        reported: 0,
+        halted: false,
+        fault: None,
+    })
+}
+
+/// Byte address from a memory reference holding a word address and an offset
+fn memory_address(args: &Value) -> Result<usize, String> {
+    let reference = args["memoryReference"].as_str().unwrap_or("");
+    let word: usize = reference
+        .parse()
+        .map_err(|_| format!("invalid memory reference {:?}", reference))?;
+    let offset = args["offset"].as_i64().unwrap_or(0);
+    word.checked_mul(WORD_BYTES)
+        .and_then(|address| address.checked_add_signed(offset as isize))
+        .ok_or_else(|| "memory address is out of range".to_string())
+}
+
+fn variable(name: &str, value: String, reference: i64) -> Value {
+    json!({ "name": name, "value": value, "variablesReference": reference })
+}
+
+fn list(values: &[i128]) -> String {
+    let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+    format!("[{}]", values.join(", "))
+}
+
+fn variables(session: &Session, reference: i64, args: &Value) -> Vec<Value> {
+    let computer = &session.computer;
+    let indexed = |values: Vec<i128>, start: usize| {
+        values
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| variable(&format!("[{}]", start + idx), value.to_string(), 0))
+            .collect()
+    };
+    match reference {
+        MACHINE_REF => {
+            let mut index = variable(
+                "index",
+                format!("{} ({})", computer.index, session.location(computer.index)),
+                0,
+            );
+            index["memoryReference"] = json!(computer.index.to_string());
+            vec![
+                index,
+                variable("relative_base", computer.relative_base.to_string(), 0),
+                variable("steps", computer.steps.to_string(), 0),
+                variable("inputs", list(&computer.inputs), INPUTS_REF),
+                variable("outputs", list(&computer.outputs), OUTPUTS_REF),
+            ]
+        }
+        INPUTS_REF => indexed(computer.inputs.clone(), 0),
+        OUTPUTS_REF => indexed(computer.outputs.clone(), 0),
+        MEMORY_REF => {
+            let len = computer.intcodes.len();
+            let start = (args["start"].as_u64().unwrap_or(0) as usize).min(len);
+            let count = args["count"].as_u64().map_or(len, |count| count as usize);
+            let end = start.saturating_add(count.min(MAX_COUNT)).min(len);
+            indexed(
+                (start..end).map(|idx| computer.intcodes[idx]).collect(),
+                start,
+            )
+        }
+        _ => Vec::new(),
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (idx, byte)| n | (*byte as u32) << (16 - 8 * idx));
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                encoded.push(BASE64[(n >> (18 - 6 * idx) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn base64_decode(encoded: &str) -> Option<Vec<u8>> {
+    let digits = encoded
+        .trim_end_matches('=')
+        .bytes()
+        .map(|c| BASE64.iter().position(|d| *d == c).map(|d| d as u32))
+        .collect::<Option<Vec<u32>>>()?;
+    let mut bytes = Vec::new();
+    for chunk in digits.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |n, (idx, digit)| n | digit << (18 - 6 * idx));
+        for idx in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * idx)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+
+    /// Runs a scripted client's requests through a server and returns every
+    /// message it sent back
+    fn script(requests: Vec<Value>) -> Vec<Value> {
+        let mut input = Vec::new();
+        for (seq, mut request) in requests.into_iter().enumerate() {
+            request["seq"] = json!(seq + 1);
+            request["type"] = json!("request");
+            let content = request.to_string();
+            write!(
+                input,
+                "Content-Length: {}\r\n\r\n{}",
+                content.len(),
+                content
+            )
+            .unwrap();
+        }
+        let mut output = Vec::new();
+        DapServer::new(&input[..], &mut output).run().unwrap();
+
+        let mut reader = DapServer::new(&output[..], Vec::new());
+        let mut messages = Vec::new();
+        while let Some(message) = reader.read_message().unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    fn response(messages: &[Value], request_seq: i64) -> &Value {
+        messages
+            .iter()
+            .find(|m| m["type"] == "response" && m["request_seq"] == request_seq)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_debug_session() {
+        let dir = env::temp_dir().join(format!("intcode-dap-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let program = dir.join("program.txt");
+        // Outputs 2 + 3 from the result cell, then 7
+        fs::write(&program, "1101,2,3,11,4,11,104,7,99,0,0,0").unwrap();
+        fs::write(dir.join("program.sym"), "print 4\nresult 11\n").unwrap();
+
+        let messages = script(vec![
+            json!({ "command": "initialize", "arguments": {} }),
+            json!({ "command": "launch", "arguments": { "program": program } }),
+            json!({
+                "command": "setFunctionBreakpoints",
+                "arguments": { "breakpoints": [{ "name": "print" }, { "name": "missing" }] },
+            }),
+            json!({ "command": "configurationDone" }),
+            json!({ "command": "stackTrace", "arguments": { "threadId": 1 } }),
+            json!({ "command": "variables", "arguments": { "variablesReference": 1 } }),
+            json!({
+                "command": "readMemory",
+                "arguments": { "memoryReference": "11", "count": 8 },
+            }),
+            json!({ "command": "next", "arguments": { "threadId": 1 } }),
+            json!({
+                "command": "writeMemory",
+                "arguments": { "memoryReference": "7", "data": "KgAAAAAAAAA=" },
+            }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+            json!({ "command": "disconnect" }),
+        ]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(messages[1]["event"], "initialized");
+        let breakpoints = &response(&messages, 3)["body"]["breakpoints"];
+        assert_eq!(breakpoints[0]["instructionReference"], "4");
+        assert_eq!(breakpoints[1]["verified"], false);
+        let frame = &response(&messages, 5)["body"]["stackFrames"][0];
+        assert_eq!(frame["name"], "print");
+        let variables = &response(&messages, 6)["body"]["variables"];
+        assert_eq!(variables[0]["value"], "4 (print)");
+        assert_eq!(variables[1]["name"], "relative_base");
+        assert_eq!(response(&messages, 7)["body"]["data"], "BQAAAAAAAAA=");
+
+        let events: Vec<String> = messages
+            .iter()
+            .filter(|m| m["type"] == "event")
+            .map(|m| match m["event"].as_str().unwrap() {
+                "stopped" => format!("stopped {}", m["body"]["reason"].as_str().unwrap()),
+                "output" => format!("output {}", m["body"]["output"].as_str().unwrap()),
+                event => event.to_string(),
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                "initialized",
+                "stopped breakpoint",
+                "output 5\n",
+                "stopped step",
+                "output 42\n",
+                "exited",
+                "terminated",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_input_takes_back_reported_output() {
+        let dir = env::temp_dir().join(format!("intcode-dap-input-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let program = dir.join("program.txt");
+        // Reads its own output back as the input and outputs again
+        fs::write(&program, "104,7,3,9,4,9,99,0,0,0").unwrap();
+        let messages = script(vec![
+            json!({
+                "command": "launch",
+                "arguments": { "program": program, "stopOnEntry": true },
+            }),
+            json!({
+                "command": "setInstructionBreakpoints",
+                "arguments": { "breakpoints": [{ "instructionReference": "2" }] },
+            }),
+            json!({ "command": "configurationDone" }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+        ]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let outputs: Vec<&Value> = messages
+            .iter()
+            .filter(|m| m["event"] == "output")
+            .map(|m| &m["body"]["output"])
+            .collect();
+        assert_eq!(outputs, vec!["7\n", "7\n"]);
+        assert!(messages.iter().any(|m| m["event"] == "exited"));
+    }
+
+    #[test]
+    fn test_message_too_long() {
+        let input = format!("Content-Length: {}\r\n\r\n", MAX_MESSAGE + 1);
+        let err = DapServer::new(input.as_bytes(), Vec::new())
+            .run()
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_memory_out_of_range() {
+        let dir = env::temp_dir().join(format!("intcode-dap-range-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let program = dir.join("program.txt");
+        fs::write(&program, "99").unwrap();
+        let read = |reference: &str, offset: i64, count: u64| {
+            json!({
+                "command": "readMemory",
+                "arguments": { "memoryReference": reference, "offset": offset, "count": count },
+            })
+        };
+        let messages = script(vec![
+            json!({ "command": "launch", "arguments": { "program": program } }),
+            read(&usize::MAX.to_string(), 0, 8),
+            read("0", -1, 8),
+            read(&(usize::MAX / WORD_BYTES).to_string(), 0, 16),
+            read("0", 0, u64::MAX),
+            json!({
+                "command": "variables",
+                "arguments": { "variablesReference": MEMORY_REF, "start": 1, "count": u64::MAX },
+            }),
+            json!({ "command": "pause", "arguments": { "threadId": 1 } }),
+        ]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        for seq in 2..=4 {
+            assert_eq!(response(&messages, seq)["success"], false);
+        }
+        let data = response(&messages, 5)["body"]["data"].as_str().unwrap();
+        assert_eq!(base64_decode(data).unwrap().len(), MAX_COUNT);
+        assert_eq!(response(&messages, 6)["body"]["variables"], json!([]));
+        assert_eq!(response(&messages, 7)["success"], false);
+    }
+
+    #[test]
+    fn test_errors_and_base64() {
+        let messages = script(vec![
+            json!({ "command": "continue" }),
+            json!({ "command": "launch", "arguments": { "program": "does-not-exist.txt" } }),
+            json!({ "command": "stepOut" }),
+        ]);
+        for seq in 1..=3 {
+            assert_eq!(response(&messages, seq)["success"], false);
+        }
+
+        for bytes in [&b""[..], b"a", b"ab", b"abc", b"\xff\x00\x10\x80"].iter() {
+            assert_eq!(
+                base64_decode(&base64_encode(bytes)).unwrap(),
+                bytes.to_vec()
+            );
+        }
+        assert_eq!(base64_encode(b"abcd"), "YWJjZA==");
+    }
+}
@@ -0,0 +1,757 @@
+use crate::computer::{instruction_len, IntcodeInstruction, IntcodeMode, IntcodeOperation};
+use crate::program::Program;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+/// How an instruction affects control flow once calls and returns are
+/// recognized
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Plain,
+    /// Stores the return address for the call right after it
+    CallStore,
+    Call {
+        target: usize,
+        ret: usize,
+    },
+    /// Jumps through the return address slot of the current frame
+    Return,
+    /// Always taken, with `None` if the target is only known at runtime
+    Jump {
+        target: Option<usize>,
+    },
+    Branch {
+        target: Option<usize>,
+    },
+    /// A jump that's never taken
+    Nop,
+    Halt,
+    /// A word with an unknown opcode or mode, kept as data
+    Invalid,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    instruction: IntcodeInstruction,
+    kind: Kind,
+    /// Relative base at this instruction relative to the one at the function
+    /// entry, if it's the same on every path
+    delta: Option<i128>,
+}
+
+#[derive(Debug)]
+struct Function {
+    entry: usize,
+    /// Words reserved by a `RelativeBaseOffset` prologue at the entry
+    frame: i128,
+    body: BTreeMap<usize, Node>,
+    /// Words of the body that the program writes at runtime
+    written: BTreeSet<usize>,
+}
+
+enum Stmt {
+    Line {
+        address: usize,
+        text: String,
+    },
+    Goto {
+        address: usize,
+        cond: Option<String>,
+        target: usize,
+    },
+    If {
+        address: usize,
+        cond: String,
+        then: Vec<Stmt>,
+        otherwise: Vec<Stmt>,
+    },
+    Loop {
+        address: usize,
+        body: Vec<Stmt>,
+        /// Condition for a `do`/`while` loop, or `None` for an infinite one
+        cond: Option<String>,
+    },
+}
+
+impl Stmt {
+    fn address(&self) -> usize {
+        match self {
+            Stmt::Line { address, .. }
+            | Stmt::Goto { address, .. }
+            | Stmt::If { address, .. }
+            | Stmt::Loop { address, .. } => *address,
+        }
+    }
+}
+
+/// Turns a program back into C-like pseudo-code. Functions are found from the
+/// calling convention Intcode compilers use: store the return address, jump to
+/// the function, which reserves a frame with `RelativeBaseOffset` and returns
+/// by jumping through the slot holding the return address. Loops and if/else
+/// are recovered from jump patterns, falling back to `goto` for anything else.
+///
+/// Memory cells are named `var<address>` unless there's a symbol for them,
+/// cells inside code are shown as `mem[<address>]`, and frame slots are named
+/// by their offset from the relative base at the function entry: `ret` for the
+/// return address, `local<n>` for the rest of the frame with arguments first,
+/// and `out<n>` for arguments to the next call.
+pub struct Decompiler {
+    words: Vec<i128>,
+    symbols: BTreeMap<usize, String>,
+}
+
+impl Decompiler {
+    pub fn new(program: &Program) -> Decompiler {
+        Decompiler {
+            words: program.image().to_vec(),
+            symbols: BTreeMap::new(),
+        }
+    }
+
+    /// Uses names from a symbol table, like the ones stored with binary
+    /// programs, for memory cells and functions
+    pub fn with_symbols(mut self, symbols: &BTreeMap<String, usize>) -> Decompiler {
+        self.symbols = symbols
+            .iter()
+            .map(|(name, address)| (*address, name.clone()))
+            .collect();
+        self
+    }
+
+    fn word(&self, address: usize) -> i128 {
+        *self.words.get(address).unwrap_or(&0)
+    }
+
+    fn classify(&self, address: usize, instruction: &IntcodeInstruction) -> Kind {
+        let param = |n: usize| self.word(address + n);
+        let static_target = |mode: IntcodeMode| match mode {
+            IntcodeMode::Immediate if param(2) >= 0 => Some(param(2) as usize),
+            _ => None,
+        };
+        match instruction.operation {
+            IntcodeOperation::Halt => Kind::Halt,
+            IntcodeOperation::JumpIfTrue | IntcodeOperation::JumpIfFalse => {
+                let target = static_target(instruction.mode_2);
+                if instruction.mode_1 != IntcodeMode::Immediate {
+                    return Kind::Branch { target };
+                }
+                let taken_if_nonzero = instruction.operation == IntcodeOperation::JumpIfTrue;
+                if (param(1) != 0) != taken_if_nonzero {
+                    return Kind::Nop;
+                }
+                match (target, instruction.mode_2) {
+                    (Some(target), _) if self.is_call_store(address.wrapping_sub(4)) => {
+                        Kind::Call {
+                            target,
+                            ret: address + 3,
+                        }
+                    }
+                    (None, IntcodeMode::Relative) => Kind::Return,
+                    _ => Kind::Jump { target },
+                }
+            }
+            _ if self.is_call_store(address) => Kind::CallStore,
+            _ => Kind::Plain,
+        }
+    }
+
+    /// Whether the instruction at `address` stores the address right after an
+    /// unconditional jump that follows it
+    fn is_call_store(&self, address: usize) -> bool {
+        let instruction = match IntcodeInstruction::try_from_num(self.word(address)) {
+            Some(instruction) if address < self.words.len() => instruction,
+            _ => return false,
+        };
+        let (a, b) = (self.word(address + 1), self.word(address + 2));
+        let value = match instruction.operation {
+            _ if instruction.mode_1 != IntcodeMode::Immediate
+                || instruction.mode_2 != IntcodeMode::Immediate =>
+            {
+                return false
+            }
+            IntcodeOperation::Add => a.checked_add(b),
+            IntcodeOperation::Multiply => a.checked_mul(b),
+            _ => return false,
+        };
+        let jump = address + 4;
+        let unconditional = match IntcodeInstruction::try_from_num(self.word(jump)) {
+            Some(next) if next.mode_1 == IntcodeMode::Immediate => match next.operation {
+                IntcodeOperation::JumpIfTrue => self.word(jump + 1) != 0,
+                IntcodeOperation::JumpIfFalse => self.word(jump + 1) == 0,
+                _ => false,
+            },
+            _ => false,
+        };
+        unconditional && value == Some(jump as i128 + 3)
+    }
+
+    /// Finds the instructions of the function starting at `entry`, along with
+    /// the entries of every function it calls
+    fn function(&self, entry: usize) -> (Function, Vec<usize>) {
+        let mut body = BTreeMap::new();
+        let mut callees = Vec::new();
+        let mut pending = vec![(entry, Some(0))];
+        while let Some((address, delta)) = pending.pop() {
+            if address >= self.words.len() {
+                continue;
+            }
+            let delta = match body.get(&address) {
+                Some(Node { delta: seen, .. }) if *seen == delta || seen.is_none() => continue,
+                // Paths disagree on the relative base, so it isn't known here
+                Some(_) => None,
+                None => delta,
+            };
+            // Unknown instructions fault, but a self-modifying program may
+            // patch the word before it's reached, so keep decoding after it
+            let (instruction, kind) = match IntcodeInstruction::try_from_num(self.word(address)) {
+                Some(instruction) => (instruction, self.classify(address, &instruction)),
+                None => (IntcodeInstruction::from_num(99), Kind::Invalid),
+            };
+            body.insert(
+                address,
+                Node {
+                    instruction,
+                    kind,
+                    delta,
+                },
+            );
+
+            let next = address + instruction_len(instruction.operation);
+            match kind {
+                Kind::Halt | Kind::Return => {}
+                Kind::Jump { target } => pending.extend(target.map(|t| (t, delta))),
+                Kind::Branch { target } => {
+                    pending.push((next, delta));
+                    pending.extend(target.map(|t| (t, delta)));
+                }
+                Kind::Call { target, ret } => {
+                    callees.push(target);
+                    pending.push((ret, delta));
+                }
+                _ if instruction.operation == IntcodeOperation::RelativeBaseOffset => {
+                    let offset = match instruction.mode_1 {
+                        IntcodeMode::Immediate => Some(self.word(address + 1)),
+                        _ => None,
+                    };
+                    pending.push((next, delta.and_then(|d| d.checked_add(offset?))));
+                }
+                _ => pending.push((next, delta)),
+            }
+        }
+
+        let frame = match body.get(&entry) {
+            Some(node)
+                if entry != 0
+                    && node.instruction.operation == IntcodeOperation::RelativeBaseOffset
+                    && node.instruction.mode_1 == IntcodeMode::Immediate =>
+            {
+                self.word(entry + 1).max(0)
+            }
+            _ => 0,
+        };
+        (
+            Function {
+                entry,
+                frame,
+                body,
+                written: BTreeSet::new(),
+            },
+            callees,
+        )
+    }
+
+    fn function_name(&self, entry: usize) -> String {
+        match self.symbols.get(&entry) {
+            Some(name) => name.clone(),
+            None if entry == 0 => "main".to_string(),
+            None => format!("f{}", entry),
+        }
+    }
+
+    fn cell_name(&self, address: i128, code: &BTreeSet<usize>) -> String {
+        if address < 0 {
+            return "0".to_string();
+        }
+        match self.symbols.get(&(address as usize)) {
+            Some(name) => name.clone(),
+            None if code.contains(&(address as usize)) => format!("mem[{}]", address),
+            None => format!("var{}", address),
+        }
+    }
+
+    fn operand(&self, f: &Function, address: usize, n: usize, code: &BTreeSet<usize>) -> String {
+        let node = &f.body[&address];
+        let value = self.word(address + n);
+        let mode = match n {
+            1 => node.instruction.mode_1,
+            2 => node.instruction.mode_2,
+            _ => node.instruction.mode_3,
+        };
+        match (mode, node.delta.and_then(|delta| delta.checked_add(value))) {
+            (IntcodeMode::Immediate, _) => value.to_string(),
+            (IntcodeMode::Position, _) => self.cell_name(value, code),
+            (IntcodeMode::Relative, None) => format!("mem[rb + {}]", value),
+            // The relative base starts at 0 in main, so its slots are globals
+            (IntcodeMode::Relative, Some(slot)) if f.entry == 0 => self.cell_name(slot, code),
+            (IntcodeMode::Relative, Some(slot)) => {
+                if slot < 0 {
+                    format!("frame[{}]", slot)
+                } else if slot == 0 {
+                    "ret".to_string()
+                } else if slot < f.frame {
+                    format!("local{}", slot)
+                } else {
+                    format!("out{}", slot - f.frame)
+                }
+            }
+        }
+    }
+
+    /// Condition a jump is taken on
+    fn jump_cond(&self, f: &Function, address: usize, code: &BTreeSet<usize>) -> String {
+        let value = self.operand(f, address, 1, code);
+        match f.body[&address].instruction.operation {
+            IntcodeOperation::JumpIfTrue => format!("{} != 0", value),
+            _ => format!("{} == 0", value),
+        }
+    }
+
+    fn negated_cond(&self, f: &Function, address: usize, code: &BTreeSet<usize>) -> String {
+        let value = self.operand(f, address, 1, code);
+        match f.body[&address].instruction.operation {
+            IntcodeOperation::JumpIfTrue => format!("{} == 0", value),
+            _ => format!("{} != 0", value),
+        }
+    }
+
+    fn statement(&self, f: &Function, address: usize, code: &BTreeSet<usize>) -> Option<String> {
+        let node = &f.body[&address];
+        let op = |n| self.operand(f, address, n, code);
+        let text = match (node.instruction.operation, node.kind) {
+            (_, Kind::CallStore) | (_, Kind::Nop) => return None,
+            (_, Kind::Call { target, .. }) => format!("{}();", self.function_name(target)),
+            // Main has no return address to jump through
+            (_, Kind::Return) if f.entry == 0 => format!("goto *{};", op(2)),
+            (_, Kind::Return) => "return;".to_string(),
+            (_, Kind::Halt) => "halt();".to_string(),
+            (_, Kind::Invalid) => format!("/* invalid opcode: {} */", self.word(address)),
+            (IntcodeOperation::Add, _) if op(2) == "0" => format!("{} = {};", op(3), op(1)),
+            (IntcodeOperation::Add, _) if op(1) == "0" => format!("{} = {};", op(3), op(2)),
+            (IntcodeOperation::Add, _) => format!("{} = {} + {};", op(3), op(1), op(2)),
+            (IntcodeOperation::Multiply, _) if op(2) == "1" => format!("{} = {};", op(3), op(1)),
+            (IntcodeOperation::Multiply, _) if op(1) == "1" => format!("{} = {};", op(3), op(2)),
+            (IntcodeOperation::Multiply, _) => format!("{} = {} * {};", op(3), op(1), op(2)),
+            (IntcodeOperation::LessThan, _) => format!("{} = {} < {};", op(3), op(1), op(2)),
+            (IntcodeOperation::Equal, _) => format!("{} = {} == {};", op(3), op(1), op(2)),
+            (IntcodeOperation::Input, _) => format!("{} = input();", op(1)),
+            (IntcodeOperation::Output, _) => format!("output({});", op(1)),
+            (IntcodeOperation::RelativeBaseOffset, _) => {
+                let is_main = f.entry == 0;
+                let prologue = !is_main && address == f.entry && f.frame > 0;
+                let offset = self.word(address + 1);
+                let epilogue = !is_main
+                    && node.instruction.mode_1 == IntcodeMode::Immediate
+                    && offset.checked_neg().is_some_and(|o| node.delta == Some(o));
+                if prologue || epilogue {
+                    return None;
+                }
+                format!("rb += {};", op(1))
+            }
+            (_, Kind::Jump { target: None }) => format!("goto *{};", op(2)),
+            _ => return None,
+        };
+        Some(text)
+    }
+
+    /// Structures the instructions of `f` between `start` and `end`. `loops`
+    /// holds the header and exit of each enclosing loop, and `header` is set
+    /// when `start` is the header of a loop that's already been opened.
+    fn structure(
+        &self,
+        f: &Function,
+        start: usize,
+        end: usize,
+        loops: &mut Vec<(usize, usize)>,
+        mut header: Option<usize>,
+        code: &BTreeSet<usize>,
+    ) -> Vec<Stmt> {
+        let mut stmts = Vec::new();
+        let mut cursor = start;
+        while let Some((&address, node)) = f.body.range(cursor..end).next() {
+            let next = address + instruction_len(node.instruction.operation);
+            cursor = next;
+
+            if header.take() != Some(address) {
+                let back_jump = f
+                    .body
+                    .range(address..end)
+                    .filter(|(_, n)| match n.kind {
+                        Kind::Jump { target } | Kind::Branch { target } => target == Some(address),
+                        _ => false,
+                    })
+                    .map(|(a, _)| *a)
+                    .next_back();
+                if let Some(back_jump) = back_jump {
+                    loops.push((address, back_jump + 3));
+                    let body = self.structure(f, address, back_jump, loops, Some(address), code);
+                    loops.pop();
+                    let cond = match f.body[&back_jump].kind {
+                        Kind::Branch { .. } => Some(self.jump_cond(f, back_jump, code)),
+                        _ => None,
+                    };
+                    stmts.push(Stmt::Loop {
+                        address,
+                        body,
+                        cond,
+                    });
+                    cursor = back_jump + 3;
+                    continue;
+                }
+            }
+
+            let patched: Vec<String> = (address..next)
+                .filter(|a| f.written.contains(a))
+                .map(|a| self.cell_name(a as i128, code))
+                .collect();
+            if !patched.is_empty() {
+                let verb = if patched.len() == 1 { "is" } else { "are" };
+                let text = format!("// {} {} written at runtime", patched.join(", "), verb);
+                stmts.push(Stmt::Line { address, text });
+            }
+
+            let innermost = loops.last().copied();
+            let loop_jump = |target: usize, cond: Option<String>| {
+                let keyword = match innermost {
+                    Some((header, _)) if header == target => "continue",
+                    Some((_, exit)) if exit == target => "break",
+                    _ => return None,
+                };
+                Some(match cond {
+                    Some(cond) => format!("if ({}) {};", cond, keyword),
+                    None => format!("{};", keyword),
+                })
+            };
+
+            match node.kind {
+                Kind::Branch {
+                    target: Some(target),
+                } if target > address && target <= end => {
+                    // The then block ends with a jump over the else block
+                    let last = f.body.range(next..target).next_back();
+                    let otherwise = match last {
+                        Some((&jump, n)) => match n.kind {
+                            Kind::Jump {
+                                target: Some(after),
+                            } if jump + 3 == target && after > target && after <= end => {
+                                Some((jump, after))
+                            }
+                            _ => None,
+                        },
+                        None => None,
+                    };
+                    let mut cond = self.negated_cond(f, address, code);
+                    let (mut then, mut otherwise) = match otherwise {
+                        Some((jump, after)) => {
+                            cursor = after;
+                            (
+                                self.structure(f, next, jump, loops, None, code),
+                                self.structure(f, target, after, loops, None, code),
+                            )
+                        }
+                        None => {
+                            cursor = target;
+                            (
+                                self.structure(f, next, target, loops, None, code),
+                                Vec::new(),
+                            )
+                        }
+                    };
+                    if then.is_empty() {
+                        cond = self.jump_cond(f, address, code);
+                        std::mem::swap(&mut then, &mut otherwise);
+                    }
+                    stmts.push(Stmt::If {
+                        address,
+                        cond,
+                        then,
+                        otherwise,
+                    });
+                }
+                Kind::Branch {
+                    target: Some(target),
+                } => {
+                    let cond = self.jump_cond(f, address, code);
+                    match loop_jump(target, Some(cond.clone())) {
+                        Some(text) => stmts.push(Stmt::Line { address, text }),
+                        None => stmts.push(Stmt::Goto {
+                            address,
+                            cond: Some(cond),
+                            target,
+                        }),
+                    }
+                }
+                Kind::Branch { target: None } => {
+                    let text = format!(
+                        "if ({}) goto *{};",
+                        self.jump_cond(f, address, code),
+                        self.operand(f, address, 2, code)
+                    );
+                    stmts.push(Stmt::Line { address, text });
+                }
+                Kind::Jump {
+                    target: Some(target),
+                } if target != next => match loop_jump(target, None) {
+                    Some(text) => stmts.push(Stmt::Line { address, text }),
+                    None => stmts.push(Stmt::Goto {
+                        address,
+                        cond: None,
+                        target,
+                    }),
+                },
+                _ => {
+                    if let Some(text) = self.statement(f, address, code) {
+                        stmts.push(Stmt::Line { address, text });
+                    }
+                }
+            }
+        }
+        stmts
+    }
+
+    fn functions(&self) -> Vec<Function> {
+        let mut functions = BTreeMap::new();
+        let mut pending = vec![0];
+        while let Some(entry) = pending.pop() {
+            if functions.contains_key(&entry) {
+                continue;
+            }
+            let (function, callees) = self.function(entry);
+            pending.extend(callees);
+            functions.insert(entry, function);
+        }
+        functions.into_values().collect()
+    }
+
+    /// Cells that instructions store to at addresses known from the image
+    fn written(&self, functions: &[Function]) -> BTreeSet<usize> {
+        functions
+            .iter()
+            .flat_map(|f| f.body.iter().map(move |(address, node)| (f, address, node)))
+            .filter_map(|(f, address, node)| {
+                let (n, mode) = match node.instruction.operation {
+                    _ if node.kind == Kind::Invalid => return None,
+                    IntcodeOperation::Add
+                    | IntcodeOperation::Multiply
+                    | IntcodeOperation::LessThan
+                    | IntcodeOperation::Equal => (3, node.instruction.mode_3),
+                    IntcodeOperation::Input => (1, node.instruction.mode_1),
+                    _ => return None,
+                };
+                let value = self.word(address + n);
+                let cell = match (mode, node.delta) {
+                    (IntcodeMode::Position, _) => value,
+                    (IntcodeMode::Relative, Some(delta)) if f.entry == 0 => {
+                        delta.checked_add(value)?
+                    }
+                    _ => return None,
+                };
+                usize::try_from(cell).ok()
+            })
+            .collect()
+    }
+
+    /// Renders the program, marking instructions whose words are written at
+    /// runtime and showing words with unknown opcodes as data
+    pub fn render(&self) -> String {
+        let mut functions = self.functions();
+        let code: BTreeSet<usize> = functions
+            .iter()
+            .flat_map(|f| {
+                f.body.iter().flat_map(|(address, node)| {
+                    *address..*address + instruction_len(node.instruction.operation)
+                })
+            })
+            .collect();
+        let written = self.written(&functions);
+        for f in &mut functions {
+            f.written = written.intersection(&code).copied().collect();
+        }
+
+        let mut out = String::new();
+        for (idx, f) in functions.iter().enumerate() {
+            if idx > 0 {
+                out.push('\n');
+            }
+            let body = self.structure(f, f.entry, usize::MAX, &mut Vec::new(), None, &code);
+            let mut targets = BTreeSet::new();
+            collect_targets(&body, &mut targets);
+            if f.frame > 0 {
+                writeln!(
+                    out,
+                    "void {}() {{ // frame of {}",
+                    self.function_name(f.entry),
+                    f.frame
+                )
+                .unwrap();
+            } else {
+                writeln!(out, "void {}() {{", self.function_name(f.entry)).unwrap();
+            }
+            print_stmts(&body, 1, &mut targets, &mut out);
+            writeln!(out, "}}").unwrap();
+        }
+        out
+    }
+}
+
+fn collect_targets(stmts: &[Stmt], targets: &mut BTreeSet<usize>) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::Goto { target, .. } => {
+                targets.insert(*target);
+            }
+            Stmt::If {
+                then, otherwise, ..
+            } => {
+                collect_targets(then, targets);
+                collect_targets(otherwise, targets);
+            }
+            Stmt::Loop { body, .. } => collect_targets(body, targets),
+            Stmt::Line { .. } => {}
+        }
+    }
+}
+
+/// Prints statements, labelling the first one at each goto target
+fn print_stmts(stmts: &[Stmt], depth: usize, labels: &mut BTreeSet<usize>, out: &mut String) {
+    let indent = "    ".repeat(depth);
+    for stmt in stmts {
+        if labels.remove(&stmt.address()) {
+            writeln!(out, "L{}:", stmt.address()).unwrap();
+        }
+        match stmt {
+            Stmt::Line { text, .. } => writeln!(out, "{}{}", indent, text).unwrap(),
+            Stmt::Goto {
+                cond: Some(cond),
+                target,
+                ..
+            } => writeln!(out, "{}if ({}) goto L{};", indent, cond, target).unwrap(),
+            Stmt::Goto {
+                cond: None, target, ..
+            } => writeln!(out, "{}goto L{};", indent, target).unwrap(),
+            Stmt::If {
+                cond,
+                then,
+                otherwise,
+                ..
+            } => {
+                writeln!(out, "{}if ({}) {{", indent, cond).unwrap();
+                print_stmts(then, depth + 1, labels, out);
+                if !otherwise.is_empty() {
+                    writeln!(out, "{}}} else {{", indent).unwrap();
+                    print_stmts(otherwise, depth + 1, labels, out);
+                }
+                writeln!(out, "{}}}", indent).unwrap();
+            }
+            Stmt::Loop {
+                body,
+                cond: Some(cond),
+                ..
+            } => {
+                writeln!(out, "{}do {{", indent).unwrap();
+                print_stmts(body, depth + 1, labels, out);
+                writeln!(out, "{}}} while ({});", indent, cond).unwrap();
+            }
+            Stmt::Loop {
+                body, cond: None, ..
+            } => {
+                writeln!(out, "{}while (1) {{", indent).unwrap();
+                print_stmts(body, depth + 1, labels, out);
+                writeln!(out, "{}}}", indent).unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_if_else_and_loop() {
+        let mut intcodes = vec![
+            3, 30, 1007, 30, 5, 31, 1005, 31, 14, 104, 1, 1105, 1, 16, 104, 0, 1001, 30, -1, 30,
+            1005, 30, 16, 99,
+        ];
+        intcodes.resize(32, 0);
+        let source = Decompiler::new(&Program::new(intcodes)).render();
+        assert_eq!(
+            source,
+            "void main() {
+    var30 = input();
+    var31 = var30 < 5;
+    if (var31 == 0) {
+        output(1);
+    } else {
+        output(0);
+    }
+    do {
+        var30 = var30 + -1;
+    } while (var30 != 0);
+    halt();
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_self_modifying_code() {
+        // Patches the invalid opcode at 7 into a halt, and the jump target at
+        // 12 with the input
+        let program = Program::new(vec![1101, 0, 99, 7, 1105, 1, 7, 42, 3, 12, 105, 1, 0]);
+        assert_eq!(
+            Decompiler::new(&program).render(),
+            "void main() {
+    mem[7] = 99;
+    // mem[7] is written at runtime
+    /* invalid opcode: 42 */
+    mem[12] = input();
+    // mem[12] is written at runtime
+    goto *mem[0];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_function_frames() {
+        // Calls a function that doubles its argument and outputs the result
+        let program = Program::new(vec![
+            109, 100, 21101, 5, 0, 1, 21101, 13, 0, 0, 1105, 1, 16, 204, 1, 99, 109, 2, 22102, 2,
+            -1, -1, 109, -2, 2106, 0, 0,
+        ]);
+        let mut computer = program.spawn(vec![], false);
+        computer.run();
+        assert_eq!(computer.outputs, vec![10]);
+
+        let mut symbols = BTreeMap::new();
+        symbols.insert("double".to_string(), 16);
+        symbols.insert("x".to_string(), 101);
+        assert_eq!(
+            Decompiler::new(&program).with_symbols(&symbols).render(),
+            "void main() {
+    rb += 100;
+    x = 5;
+    double();
+    output(x);
+    halt();
+}
+
+void double() { // frame of 2
+    local1 = 2 * local1;
+    return;
+}
+"
+        );
+    }
+}
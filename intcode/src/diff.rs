@@ -0,0 +1,212 @@
+use crate::computer::{instruction_len, IntcodeComputer, IntcodeInstruction, IntcodeOperation};
+use crate::memory::Memory;
+use crate::program::Program;
+use crate::transpile::reachable_instructions;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Where a changed cell sits inside an instruction decoded from the earlier
+/// state
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CodeCell {
+    /// Address of the instruction's opcode
+    pub instruction: usize,
+    pub operation: IntcodeOperation,
+    /// 0 for the opcode itself, otherwise the parameter number
+    pub offset: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellChange {
+    pub address: usize,
+    pub old: i128,
+    pub new: i128,
+    pub code: Option<CodeCell>,
+}
+
+/// Contiguous run of changed cells
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangedRange {
+    pub start: usize,
+    pub cells: Vec<CellChange>,
+}
+
+impl ChangedRange {
+    /// Last address in the range
+    pub fn end(&self) -> usize {
+        self.start + self.cells.len() - 1
+    }
+}
+
+/// Cells that differ between two memory states. Memory past the end of either
+/// state counts as 0, the same as the machine sees it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemoryDiff {
+    pub ranges: Vec<ChangedRange>,
+}
+
+impl MemoryDiff {
+    pub fn new(before: &Memory, after: &Memory) -> MemoryDiff {
+        let code = code_cells(&reachable_instructions(&Program::new(before.to_vec())));
+        let mut ranges: Vec<ChangedRange> = Vec::new();
+        for address in 0..before.len().max(after.len()) {
+            let old = *before.get(address).unwrap_or(&0);
+            let new = *after.get(address).unwrap_or(&0);
+            if old == new {
+                continue;
+            }
+            let change = CellChange {
+                address,
+                old,
+                new,
+                code: code.get(&address).copied(),
+            };
+            match ranges.last_mut() {
+                Some(range) if range.end() + 1 == address => range.cells.push(change),
+                _ => ranges.push(ChangedRange {
+                    start: address,
+                    cells: vec![change],
+                }),
+            }
+        }
+        MemoryDiff { ranges }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn changes(&self) -> impl Iterator<Item = &CellChange> {
+        self.ranges.iter().flat_map(|range| range.cells.iter())
+    }
+
+    /// Changes to cells inside decoded instructions, where the program
+    /// modified its own code
+    pub fn code_changes(&self) -> impl Iterator<Item = &CellChange> {
+        self.changes().filter(|change| change.code.is_some())
+    }
+}
+
+fn code_cells(instructions: &BTreeMap<usize, IntcodeInstruction>) -> BTreeMap<usize, CodeCell> {
+    let mut cells = BTreeMap::new();
+    for (address, instruction) in instructions.iter() {
+        for offset in 0..instruction_len(instruction.operation) {
+            cells.insert(
+                address + offset,
+                CodeCell {
+                    instruction: *address,
+                    operation: instruction.operation,
+                    offset,
+                },
+            );
+        }
+    }
+    cells
+}
+
+impl IntcodeComputer {
+    /// Compares this machine's memory with a later snapshot of it
+    pub fn diff(&self, later: &IntcodeComputer) -> MemoryDiff {
+        MemoryDiff::new(&self.intcodes, &later.intcodes)
+    }
+}
+
+impl Program {
+    /// Compares the program image with a machine's current memory
+    pub fn diff(&self, computer: &IntcodeComputer) -> MemoryDiff {
+        MemoryDiff::new(self.image(), &computer.intcodes)
+    }
+}
+
+impl fmt::Display for CodeCell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.offset {
+            0 => write!(f, "opcode of {:?} at {}", self.operation, self.instruction),
+            n => write!(
+                f,
+                "parameter {} of {:?} at {}",
+                n, self.operation, self.instruction
+            ),
+        }
+    }
+}
+
+/// Lists each range followed by its cells, for example:
+///
+/// ```text
+/// 1..=2
+///   1: 0 -> 12 (parameter 1 of Add at 0)
+///   2: 0 -> 2 (parameter 2 of Add at 0)
+/// ```
+impl fmt::Display for MemoryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for range in self.ranges.iter() {
+            if range.cells.len() == 1 {
+                writeln!(f, "{}", range.start)?;
+            } else {
+                writeln!(f, "{}..={}", range.start, range.end())?;
+            }
+            for change in range.cells.iter() {
+                write!(f, "  {}: {} -> {}", change.address, change.old, change.new)?;
+                match change.code {
+                    Some(code) => writeln!(f, " ({})", code)?,
+                    None => writeln!(f)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_patched_parameters() {
+        let program = Program::new(vec![1, 0, 0, 3, 99]);
+        let mut computer = program.spawn(vec![], false);
+        computer.intcodes[1] = 4;
+        computer.intcodes[2] = 4;
+        let diff = program.diff(&computer);
+        assert_eq!(diff.ranges.len(), 1);
+        assert_eq!(
+            diff.to_string(),
+            "1..=2\n  1: 0 -> 4 (parameter 1 of Add at 0)\n  2: 0 -> 4 (parameter 2 of Add at 0)\n"
+        );
+
+        let before = computer.clone();
+        computer.run();
+        let diff = before.diff(&computer);
+        assert_eq!(
+            diff.to_string(),
+            "3\n  3: 3 -> 198 (parameter 3 of Add at 0)\n"
+        );
+    }
+
+    #[test]
+    fn test_self_modifying_and_growth() {
+        // Turns the add at 4 into a multiply that writes past the end
+        let program = Program::new(vec![1101, 1, 1, 4, 1, 9, 9, 12, 99, 58]);
+        let mut computer = program.spawn(vec![], false);
+        computer.run();
+        let diff = program.diff(&computer);
+        let changes: Vec<(usize, i128, i128)> = diff
+            .changes()
+            .map(|change| (change.address, change.old, change.new))
+            .collect();
+        assert_eq!(changes, vec![(4, 1, 2), (12, 0, 3364)]);
+        assert_eq!(diff.ranges.len(), 2);
+
+        let code: Vec<&CellChange> = diff.code_changes().collect();
+        assert_eq!(code.len(), 1);
+        assert_eq!(
+            code[0].code,
+            Some(CodeCell {
+                instruction: 4,
+                operation: IntcodeOperation::Add,
+                offset: 0,
+            })
+        );
+    }
+}
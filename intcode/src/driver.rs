@@ -0,0 +1,355 @@
+use crate::computer::{IntcodeComputer, IntcodeOperation};
+use crate::error::IntcodeError;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+/// Instructions a machine runs without input or output before letting other
+/// tasks on the executor run
+const YIELD_STEPS: usize = 1000;
+
+/// Supplies inputs to a machine run with `run_async`
+pub trait AsyncInput {
+    /// `Ready(None)` means no more inputs will come
+    fn poll_input(&mut self, cx: &mut Context<'_>) -> Poll<Option<i128>>;
+}
+
+/// Takes outputs from a machine run with `run_async`. A pending output is
+/// polled again with the same value.
+pub trait AsyncOutput {
+    fn poll_output(&mut self, cx: &mut Context<'_>, value: i128) -> Poll<()>;
+}
+
+impl<T: AsyncInput + ?Sized> AsyncInput for &mut T {
+    fn poll_input(&mut self, cx: &mut Context<'_>) -> Poll<Option<i128>> {
+        (**self).poll_input(cx)
+    }
+}
+
+impl<T: AsyncOutput + ?Sized> AsyncOutput for &mut T {
+    fn poll_output(&mut self, cx: &mut Context<'_>, value: i128) -> Poll<()> {
+        (**self).poll_output(cx, value)
+    }
+}
+
+impl AsyncOutput for Vec<i128> {
+    fn poll_output(&mut self, _cx: &mut Context<'_>, value: i128) -> Poll<()> {
+        self.push(value);
+        Poll::Ready(())
+    }
+}
+
+impl IntcodeComputer {
+    /// Runs the machine, waiting on `input` whenever `inputs` is empty and the
+    /// attached input source has nothing at an `Input` instruction, and sending
+    /// every output to `output` instead of `outputs`. A custom opcode 3 is left
+    /// to its handler. Returns `Halt`, or `Input` if the input ran out, with the
+    /// machine left at that instruction.
+    pub async fn run_async<I: AsyncInput, O: AsyncOutput>(
+        &mut self,
+        mut input: I,
+        mut output: O,
+    ) -> Result<IntcodeOperation, IntcodeError> {
+        let mut quiet_steps = 0;
+        loop {
+            let opcode = self.intcodes.get(self.index).copied().unwrap_or(0);
+            if opcode % 100 == 3
+                && self.opcodes.get(3).is_none()
+                && self.inputs.is_empty()
+                && self.pulled_input.is_none()
+            {
+                self.pulled_input = self.input_source.next_input(self.steps);
+                if self.pulled_input.is_none() {
+                    match poll_fn(|cx| input.poll_input(cx)).await {
+                        Some(value) => self.inputs.push(value),
+                        None => return Ok(IntcodeOperation::Input),
+                    }
+                }
+                quiet_steps = 0;
+            }
+            match self.try_step()? {
+                Some(IntcodeOperation::Halt) => return Ok(IntcodeOperation::Halt),
+                Some(IntcodeOperation::Output) => {
+                    let value = self.outputs.pop().unwrap_or(0);
+                    poll_fn(|cx| output.poll_output(cx, value)).await;
+                    quiet_steps = 0;
+                }
+                _ => {
+                    quiet_steps += 1;
+                    if quiet_steps == YIELD_STEPS {
+                        yield_now().await;
+                        quiet_steps = 0;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Lets the executor run other tasks before coming back to this one
+async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[derive(Debug, Default)]
+struct ChannelState {
+    values: VecDeque<i128>,
+    senders: usize,
+    waker: Option<Waker>,
+}
+
+/// Sends values to the `Receiver` of a channel. The channel closes once every
+/// sender is dropped.
+#[derive(Debug)]
+pub struct Sender(Rc<RefCell<ChannelState>>);
+
+/// Receives values sent on a channel, in order
+#[derive(Debug)]
+pub struct Receiver(Rc<RefCell<ChannelState>>);
+
+/// Unbounded single-threaded channel for connecting machines on an `Executor`
+pub fn channel() -> (Sender, Receiver) {
+    let state = Rc::new(RefCell::new(ChannelState {
+        senders: 1,
+        ..ChannelState::default()
+    }));
+    (Sender(state.clone()), Receiver(state))
+}
+
+impl Sender {
+    pub fn send(&self, value: i128) {
+        let mut state = self.0.borrow_mut();
+        state.values.push_back(value);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Clone for Sender {
+    fn clone(&self) -> Sender {
+        self.0.borrow_mut().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        let mut state = self.0.borrow_mut();
+        state.senders -= 1;
+        if state.senders == 0 {
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl AsyncOutput for Sender {
+    fn poll_output(&mut self, _cx: &mut Context<'_>, value: i128) -> Poll<()> {
+        self.send(value);
+        Poll::Ready(())
+    }
+}
+
+impl Receiver {
+    /// Takes a value that has already been sent, without waiting
+    pub fn try_recv(&self) -> Option<i128> {
+        self.0.borrow_mut().values.pop_front()
+    }
+}
+
+impl AsyncInput for Receiver {
+    fn poll_input(&mut self, cx: &mut Context<'_>) -> Poll<Option<i128>> {
+        let mut state = self.0.borrow_mut();
+        match state.values.pop_front() {
+            Some(value) => Poll::Ready(Some(value)),
+            None if state.senders == 0 => Poll::Ready(None),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+type Task<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+struct TaskWaker {
+    id: usize,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.ready.lock().unwrap().push_back(self.id);
+    }
+}
+
+/// Runs tasks, such as machines driven by `run_async`, on the current thread
+#[derive(Default)]
+pub struct Executor<'a> {
+    tasks: Vec<Option<Task<'a>>>,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl<'a> Executor<'a> {
+    pub fn new() -> Executor<'a> {
+        Executor::default()
+    }
+
+    pub fn spawn<F: Future<Output = ()> + 'a>(&mut self, task: F) {
+        self.ready.lock().unwrap().push_back(self.tasks.len());
+        self.tasks.push(Some(Box::pin(task)));
+    }
+
+    /// Polls tasks until none can make progress, and returns how many are
+    /// still unfinished, which are waiting on something that never came
+    pub fn run(&mut self) -> usize {
+        loop {
+            let id = match self.ready.lock().unwrap().pop_front() {
+                Some(id) => id,
+                None => break,
+            };
+            let task = match &mut self.tasks[id] {
+                Some(task) => task,
+                None => continue,
+            };
+            let waker = Waker::from(Arc::new(TaskWaker {
+                id,
+                ready: self.ready.clone(),
+            }));
+            if task
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker))
+                .is_ready()
+            {
+                self.tasks[id] = None;
+            }
+        }
+        self.tasks.iter().filter(|task| task.is_some()).count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::Program;
+    use crate::replay::InputSource;
+    use std::cell::Cell;
+
+    /// Forwards outputs to a channel and keeps the last one
+    struct Tap(Sender, Rc<Cell<i128>>);
+
+    impl AsyncOutput for Tap {
+        fn poll_output(&mut self, cx: &mut Context<'_>, value: i128) -> Poll<()> {
+            self.1.set(value);
+            self.0.poll_output(cx, value)
+        }
+    }
+
+    #[test]
+    fn test_feedback_loop() {
+        let program = Program::new(vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ]);
+        let phases = [9, 8, 7, 6, 5];
+        let (senders, receivers): (Vec<Sender>, Vec<Receiver>) =
+            phases.iter().map(|_| channel()).unzip();
+        for (sender, phase) in senders.iter().zip(phases.iter()) {
+            sender.send(*phase);
+        }
+        senders[0].send(0);
+
+        let signal = Rc::new(Cell::new(0));
+        let mut executor = Executor::new();
+        let mut senders: VecDeque<Sender> = senders.into_iter().collect();
+        senders.rotate_left(1);
+        for (n, (receiver, sender)) in receivers.into_iter().zip(senders).enumerate() {
+            let mut computer = program.spawn(vec![], false);
+            let signal = signal.clone();
+            executor.spawn(async move {
+                let halt = if n == phases.len() - 1 {
+                    computer.run_async(receiver, Tap(sender, signal)).await
+                } else {
+                    computer.run_async(receiver, sender).await
+                };
+                assert_eq!(halt, Ok(IntcodeOperation::Halt));
+            });
+        }
+        assert_eq!(executor.run(), 0);
+        assert_eq!(signal.get(), 139629729);
+    }
+
+    #[test]
+    fn test_waiting_and_closed_inputs() {
+        // Adds up inputs forever
+        let program = Program::new(vec![3, 11, 1, 11, 12, 12, 4, 12, 1105, 1, 0, 0, 0]);
+        let (sender, receiver) = channel();
+        let mut outputs = Vec::new();
+        let mut stopped = None;
+        let mut executor = Executor::new();
+        executor.spawn(async {
+            let mut computer = program.spawn(vec![], false);
+            stopped = Some(computer.run_async(receiver, &mut outputs).await);
+        });
+        // Nothing to read yet, so the machine waits without finishing
+        assert_eq!(executor.run(), 1);
+
+        sender.send(2);
+        sender.send(3);
+        assert_eq!(executor.run(), 1);
+        drop(sender);
+        assert_eq!(executor.run(), 0);
+        drop(executor);
+        assert_eq!(outputs, vec![2, 5]);
+        assert_eq!(stopped, Some(Ok(IntcodeOperation::Input)));
+    }
+
+    struct Scripted(VecDeque<i128>);
+
+    impl InputSource for Scripted {
+        fn next_input(&mut self, _step: usize) -> Option<i128> {
+            self.0.pop_front()
+        }
+    }
+
+    #[test]
+    fn test_input_source_first() {
+        // Adds up inputs forever
+        let program = Program::new(vec![3, 11, 1, 11, 12, 12, 4, 12, 1105, 1, 0, 0, 0]);
+        let (sender, receiver) = channel();
+        sender.send(10);
+        drop(sender);
+        let mut outputs = Vec::new();
+        let mut computer = program.spawn(vec![], false);
+        computer.set_input_source(Arc::new(Mutex::new(Scripted(vec![2, 3].into()))));
+        let mut executor = Executor::new();
+        executor.spawn(async {
+            let stopped = computer.run_async(receiver, &mut outputs).await;
+            assert_eq!(stopped, Ok(IntcodeOperation::Input));
+        });
+        assert_eq!(executor.run(), 0);
+        drop(executor);
+        assert_eq!(outputs, vec![2, 5, 15]);
+    }
+}
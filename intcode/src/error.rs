@@ -0,0 +1,70 @@
+use std::error::Error;
+use std::fmt;
+
+/// Faults a machine can stop with instead of carrying on. `index` is the
+/// address of the faulting instruction and `param` the parameter that made the
+/// access, with 0 for fetching the instruction itself.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IntcodeError {
+    NegativeAddress {
+        index: usize,
+        param: usize,
+        address: i128,
+    },
+    /// An access at or past the memory limit or the strict addressing limit
+    OutOfBounds {
+        index: usize,
+        param: usize,
+        address: i128,
+        limit: usize,
+    },
+    /// A parameter mode digit other than 0, 1 or 2
+    InvalidMode {
+        index: usize,
+        param: usize,
+        mode: i128,
+    },
+    /// Arithmetic or a relative address that doesn't fit in an `i128`
+    Overflow { index: usize },
+    /// A word with valid modes but no known opcode
+    InvalidInstruction { index: usize, instruction: i128 },
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntcodeError::NegativeAddress {
+                index,
+                param,
+                address,
+            } => write!(
+                f,
+                "negative address {} in parameter {} of the instruction at {}",
+                address, param, index
+            ),
+            IntcodeError::OutOfBounds {
+                index,
+                param,
+                address,
+                limit,
+            } => write!(
+                f,
+                "address {} past the limit of {} in parameter {} of the instruction at {}",
+                address, limit, param, index
+            ),
+            IntcodeError::InvalidMode { index, param, mode } => write!(
+                f,
+                "invalid mode {} for parameter {} of the instruction at {}",
+                mode, param, index
+            ),
+            IntcodeError::Overflow { index } => {
+                write!(f, "overflow in the instruction at {}", index)
+            }
+            IntcodeError::InvalidInstruction { index, instruction } => {
+                write!(f, "invalid instruction {} at {}", instruction, index)
+            }
+        }
+    }
+}
+
+impl Error for IntcodeError {}
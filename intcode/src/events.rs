@@ -0,0 +1,299 @@
+use crate::computer::{IntcodeComputer, IntcodeOperation};
+use crate::error::IntcodeError;
+use std::collections::VecDeque;
+use std::mem;
+
+/// An output along with when and where the program produced it
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputEvent {
+    pub value: i128,
+    /// Number of instructions executed before the one that produced it
+    pub step: usize,
+    /// Address of the instruction that produced it
+    pub index: usize,
+    /// Inputs consumed since the previous output
+    pub inputs: Vec<i128>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EventLog {
+    events: VecDeque<OutputEvent>,
+    inputs: Vec<i128>,
+}
+
+impl EventLog {
+    pub(crate) fn on_input(&mut self, value: i128) {
+        self.inputs.push(value);
+    }
+
+    pub(crate) fn on_output(&mut self, value: i128, step: usize, index: usize) {
+        self.events.push_back(OutputEvent {
+            value,
+            step,
+            index,
+            inputs: mem::take(&mut self.inputs),
+        });
+    }
+}
+
+impl IntcodeComputer {
+    /// Records an `OutputEvent` for every output from now on, alongside the
+    /// values pushed onto `outputs`. Inputs read by custom opcodes aren't
+    /// included in the events.
+    pub fn record_output_events(&mut self) {
+        if self.output_events.is_none() {
+            self.output_events = Some(EventLog::default());
+        }
+    }
+
+    /// Recorded events that haven't been taken yet
+    pub fn output_events(&self) -> impl Iterator<Item = &OutputEvent> {
+        self.output_events.iter().flat_map(|log| log.events.iter())
+    }
+
+    pub fn take_output_events(&mut self) -> Vec<OutputEvent> {
+        match &mut self.output_events {
+            Some(log) => log.events.drain(..).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Runs the machine only as far as needed to produce each event, ending
+    /// when it halts or after the first fault. Turns on event recording, and
+    /// takes events recorded before it was created first. With pause on
+    /// input set, it also ends when the machine waits for input, and can be
+    /// picked up again once there is some.
+    pub fn output_stream(&mut self) -> OutputStream<'_> {
+        self.record_output_events();
+        OutputStream {
+            computer: self,
+            done: false,
+        }
+    }
+
+    /// Runs the machine only as far as needed to produce each output, ending
+    /// when it halts or after the first fault. With pause on input set, it
+    /// also ends when the machine waits for input, and carries on from there
+    /// after `push_input`.
+    pub fn outputs_iter(self) -> Outputs {
+        Outputs {
+            taken: self.outputs.len(),
+            computer: self,
+            done: false,
+        }
+    }
+}
+
+/// Outputs of a machine produced on demand, see `outputs_iter`
+pub struct Outputs {
+    computer: IntcodeComputer,
+    /// Number of values in `outputs` already returned
+    taken: usize,
+    done: bool,
+}
+
+impl Outputs {
+    /// Queues an input for the machine, such as a reply to the last output
+    pub fn push_input(&mut self, value: i128) {
+        self.computer.inputs.push(value);
+    }
+
+    pub fn computer(&self) -> &IntcodeComputer {
+        &self.computer
+    }
+
+    pub fn into_computer(self) -> IntcodeComputer {
+        self.computer
+    }
+}
+
+impl Iterator for Outputs {
+    type Item = Result<i128, IntcodeError>;
+
+    fn next(&mut self) -> Option<Result<i128, IntcodeError>> {
+        while !self.done {
+            // An input can take back the last output when nothing else is
+            // queued, so the count can shrink
+            self.taken = self.taken.min(self.computer.outputs.len());
+            if let Some(value) = self.computer.outputs.get(self.taken) {
+                self.taken += 1;
+                return Some(Ok(*value));
+            }
+            let index = self.computer.index;
+            match self.computer.try_step() {
+                Ok(Some(IntcodeOperation::Halt)) => self.done = true,
+                Ok(Some(IntcodeOperation::Input)) if self.computer.index == index => return None,
+                Ok(_) => {}
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Output events of a machine produced on demand, see `output_stream`
+pub struct OutputStream<'a> {
+    computer: &'a mut IntcodeComputer,
+    done: bool,
+}
+
+impl<'a> Iterator for OutputStream<'a> {
+    type Item = Result<OutputEvent, IntcodeError>;
+
+    fn next(&mut self) -> Option<Result<OutputEvent, IntcodeError>> {
+        while !self.done {
+            let log = self.computer.output_events.as_mut()?;
+            if let Some(event) = log.events.pop_front() {
+                return Some(Ok(event));
+            }
+            let index = self.computer.index;
+            match self.computer.try_step() {
+                Ok(Some(IntcodeOperation::Halt)) => self.done = true,
+                Ok(Some(IntcodeOperation::Input)) if self.computer.index == index => return None,
+                Ok(_) => {}
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::replay::InputSource;
+    use std::sync::{Arc, Mutex};
+
+    // Reads two values and outputs their sum, then reads one and outputs it
+    // twice
+    fn program() -> Vec<i128> {
+        vec![3, 20, 3, 21, 1, 20, 21, 22, 4, 22, 3, 20, 4, 20, 4, 20, 99]
+    }
+
+    #[test]
+    fn test_recorded_events() {
+        let mut computer = IntcodeComputer::new(program(), vec![2, 3, 7], false);
+        computer.record_output_events();
+        computer.run();
+        assert_eq!(computer.outputs, vec![5, 7, 7]);
+        let events: Vec<OutputEvent> = computer.take_output_events();
+        assert_eq!(
+            events,
+            vec![
+                OutputEvent {
+                    value: 5,
+                    step: 3,
+                    index: 8,
+                    inputs: vec![2, 3],
+                },
+                OutputEvent {
+                    value: 7,
+                    step: 5,
+                    index: 12,
+                    inputs: vec![7],
+                },
+                OutputEvent {
+                    value: 7,
+                    step: 6,
+                    index: 14,
+                    inputs: vec![],
+                },
+            ]
+        );
+        assert_eq!(computer.output_events().count(), 0);
+    }
+
+    /// Hands out inputs and counts how many were asked for
+    struct Counted(Arc<Mutex<usize>>);
+
+    impl InputSource for Counted {
+        fn next_input(&mut self, _step: usize) -> Option<i128> {
+            let mut count = self.0.lock().unwrap();
+            *count += 1;
+            Some(*count as i128)
+        }
+    }
+
+    #[test]
+    fn test_stream_is_lazy() {
+        let count = Arc::new(Mutex::new(0));
+        let mut computer = IntcodeComputer::new(program(), vec![], false);
+        computer.set_input_source(Arc::new(Mutex::new(Counted(count.clone()))));
+
+        let mut stream = computer.output_stream();
+        let first = stream.next().unwrap().unwrap();
+        assert_eq!((first.value, first.inputs), (3, vec![1, 2]));
+        assert_eq!(*count.lock().unwrap(), 2);
+
+        let rest: Vec<(i128, usize)> = stream
+            .map(|event| event.map(|event| (event.value, event.index)).unwrap())
+            .collect();
+        assert_eq!(rest, vec![(3, 12), (3, 14)]);
+        assert_eq!(*count.lock().unwrap(), 3);
+        assert_eq!(computer.outputs, vec![3, 3, 3]);
+    }
+
+    #[test]
+    fn test_stream_fault() {
+        let mut computer = IntcodeComputer::new(vec![104, 1, 4, -1, 99], vec![], false);
+        computer.set_addressing(crate::computer::AddressingPolicy::Strict { limit: 10 });
+        let events: Vec<Result<OutputEvent, IntcodeError>> = computer.output_stream().collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].as_ref().map(|event| event.value), Ok(1));
+        assert!(matches!(
+            events[1],
+            Err(IntcodeError::NegativeAddress { index: 2, .. })
+        ));
+        assert_eq!(computer.index, 2);
+    }
+
+    #[test]
+    fn test_pause_on_input() {
+        let mut computer = IntcodeComputer::new(program(), vec![2, 3], false);
+        computer.set_pause_on_input(true);
+        let values: Vec<i128> = computer
+            .output_stream()
+            .map(|event| event.unwrap().value)
+            .collect();
+        assert_eq!(values, vec![5]);
+        computer.inputs.push(4);
+        assert_eq!(computer.output_stream().count(), 2);
+
+        let mut computer = IntcodeComputer::new(program(), vec![2, 3], false);
+        computer.set_pause_on_input(true);
+        let mut outputs = computer.outputs_iter();
+        assert_eq!(outputs.by_ref().collect::<Vec<_>>(), vec![Ok(5)]);
+        assert_eq!(outputs.computer().index, 10);
+        outputs.push_input(4);
+        assert_eq!(outputs.collect::<Vec<_>>(), vec![Ok(4), Ok(4)]);
+    }
+
+    #[test]
+    fn test_outputs_iter() {
+        let computer = IntcodeComputer::new(program(), vec![2, 3, 7], false);
+        let outputs: Result<Vec<i128>, IntcodeError> = computer.outputs_iter().collect();
+        assert_eq!(outputs, Ok(vec![5, 7, 7]));
+
+        // Stops early, leaving the machine at the instruction after the output
+        let mut outputs = IntcodeComputer::new(program(), vec![2, 3], false).outputs_iter();
+        assert_eq!(outputs.next(), Some(Ok(5)));
+        assert_eq!(outputs.computer().index, 10);
+        outputs.push_input(4);
+        assert_eq!(outputs.take(2).collect::<Vec<_>>(), vec![Ok(4), Ok(4)]);
+
+        let mut computer = IntcodeComputer::new(vec![104, 1, 4, -1, 99], vec![], false);
+        computer.set_addressing(crate::computer::AddressingPolicy::Strict { limit: 10 });
+        let outputs: Vec<Result<i128, IntcodeError>> = computer.outputs_iter().collect();
+        assert_eq!(outputs.len(), 2);
+        assert!(matches!(
+            outputs[1],
+            Err(IntcodeError::NegativeAddress { index: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::computer::{check_address, IntcodeComputer};
+use crate::error::IntcodeError;
+use crate::memory::Memory;
+use crate::observer::ObserverSlot;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Whether a parameter is read as a value or used as an address to write to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParamRole {
+    Read,
+    Write,
+}
+
+/// What a custom opcode handler can see and change while it runs
+pub struct CustomContext<'a> {
+    pub intcodes: &'a mut Memory,
+    pub inputs: &'a mut Vec<i128>,
+    pub outputs: &'a mut Vec<i128>,
+    pub index: usize,
+    pub relative_base: i128,
+    /// Values for read parameters and addresses for write parameters
+    params: Vec<i128>,
+    roles: &'a [ParamRole],
+    limit: usize,
+    jump: Option<usize>,
+    /// First bad write, which faults the instruction
+    fault: Option<IntcodeError>,
+    observer: &'a ObserverSlot,
+}
+
+impl<'a> CustomContext<'a> {
+    pub fn param(&self, n: usize) -> i128 {
+        self.params[n]
+    }
+
+    /// Writes `value` to the address given by parameter `n`. A negative
+    /// address or one past the machine's limit isn't written, and faults the
+    /// instruction once the handler returns.
+    ///
+    /// Panics if parameter `n` wasn't registered as a write parameter.
+    pub fn write(&mut self, n: usize, value: i128) -> Result<(), IntcodeError> {
+        if self.roles.get(n) != Some(&ParamRole::Write) {
+            panic!("Parameter {} is not a write parameter", n);
+        }
+        let checked = check_address(self.index, n + 1, self.params[n], self.limit);
+        if let Err(err) = &checked {
+            self.fault.get_or_insert_with(|| err.clone());
+        }
+        checked?;
+        let address = self.params[n] as usize;
+        self.intcodes.extend(address + 1);
+        let old = self.intcodes[address];
+        self.intcodes[address] = value;
+        self.observer
+            .notify(|o| o.on_memory_write(address, old, value));
+        Ok(())
+    }
+
+    /// Continues at `address` instead of the next instruction
+    pub fn jump_to(&mut self, address: usize) {
+        self.jump = Some(address);
+    }
+}
+
+type Handler = dyn Fn(&mut CustomContext) + Send + Sync;
+
+#[derive(Clone)]
+pub struct CustomOpcode {
+    pub name: String,
+    pub params: Vec<ParamRole>,
+    handler: Arc<Handler>,
+}
+
+impl fmt::Debug for CustomOpcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CustomOpcode")
+            .field("name", &self.name)
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+/// Custom opcodes registered on a machine, keyed by opcode number
+#[derive(Clone, Debug, Default)]
+pub struct OpcodeRegistry {
+    opcodes: HashMap<i128, Arc<CustomOpcode>>,
+}
+
+impl OpcodeRegistry {
+    pub fn get(&self, opcode: i128) -> Option<&Arc<CustomOpcode>> {
+        self.opcodes.get(&opcode)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.opcodes.is_empty()
+    }
+}
+
+/// Whether an opcode number is taken by a built-in operation
+fn is_builtin(opcode: i128) -> bool {
+    (1..=9).contains(&opcode) || opcode == 99
+}
+
+impl IntcodeComputer {
+    /// Registers a custom instruction. Parameter modes work the same as for
+    /// built-in instructions, so a parameter's mode comes from the digit above
+    /// the previous parameter's.
+    ///
+    /// Panics if `opcode` isn't a two digit number or is already used by a
+    /// built-in instruction.
+    pub fn register_opcode<F>(
+        &mut self,
+        opcode: i128,
+        name: &str,
+        params: Vec<ParamRole>,
+        handler: F,
+    ) where
+        F: Fn(&mut CustomContext) + Send + Sync + 'static,
+    {
+        if !(1..100).contains(&opcode) || is_builtin(opcode) {
+            panic!("Opcode {} is not available for custom instructions", opcode);
+        }
+        self.opcodes.opcodes.insert(
+            opcode,
+            Arc::new(CustomOpcode {
+                name: name.to_string(),
+                params,
+                handler: Arc::new(handler),
+            }),
+        );
+    }
+
+    pub(crate) fn handle_custom(&mut self, opcode: &CustomOpcode) -> Result<(), IntcodeError> {
+        let num = self.intcodes[self.index];
+        let mut params = Vec::with_capacity(opcode.params.len());
+        for (n, role) in opcode.params.iter().enumerate() {
+            let mode = self.mode(num, n + 1)?;
+            params.push(self.resolve(n + 1, mode, *role == ParamRole::Write)?);
+        }
+
+        let outputs = self.outputs.len();
+        let limit = self.limit();
+        let mut context = CustomContext {
+            intcodes: &mut self.intcodes,
+            inputs: &mut self.inputs,
+            outputs: &mut self.outputs,
+            index: self.index,
+            relative_base: self.relative_base,
+            params,
+            roles: &opcode.params,
+            limit,
+            jump: None,
+            fault: None,
+            observer: &self.observer,
+        };
+        (opcode.handler)(&mut context);
+        let jump = context.jump;
+        let fault = context.fault;
+        if let Some(log) = &mut self.output_events {
+            for value in self.outputs[outputs.min(self.outputs.len())..].iter() {
+                log.on_output(*value, self.steps, self.index);
+            }
+        }
+        if let Some(err) = fault {
+            return Err(err);
+        }
+        self.index = jump.unwrap_or(self.index + opcode.params.len() + 1);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::computer::IntcodeOperation;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_multiply_add() {
+        // mem[9] = 3 * mem[8] + 4, then output mem[9]
+        let mut computer =
+            IntcodeComputer::new(vec![10120, 3, 8, 4, 9, 4, 9, 99, 5, 0], vec![], false);
+        computer.register_opcode(
+            20,
+            "madd",
+            vec![
+                ParamRole::Read,
+                ParamRole::Read,
+                ParamRole::Read,
+                ParamRole::Write,
+            ],
+            |ctx| {
+                let value = ctx.param(0) * ctx.param(1) + ctx.param(2);
+                ctx.write(3, value).unwrap();
+            },
+        );
+        computer.run();
+        assert_eq!(computer.outputs, vec![19]);
+    }
+
+    #[test]
+    fn test_debug_print_and_jump() {
+        let printed = Arc::new(Mutex::new(Vec::new()));
+        let log = printed.clone();
+        // Prints position 8 and its address, skips the output and halts
+        let mut computer =
+            IntcodeComputer::new(vec![30, 8, 131, 6, 104, 1, 99, 0, 42], vec![], false);
+        computer.register_opcode(30, "dbg", vec![ParamRole::Read], move |ctx| {
+            log.lock().unwrap().push((ctx.index, ctx.param(0)));
+        });
+        computer.register_opcode(31, "jmp", vec![ParamRole::Read], |ctx| {
+            let address = ctx.param(0) as usize;
+            ctx.jump_to(address);
+        });
+        assert_eq!(computer.run(), IntcodeOperation::Halt);
+        assert_eq!(*printed.lock().unwrap(), vec![(0, 42)]);
+        assert!(computer.outputs.is_empty());
+    }
+
+    fn register_store(computer: &mut IntcodeComputer, role: ParamRole) {
+        computer.register_opcode(40, "store", vec![role], |ctx| {
+            let _ = ctx.write(0, 7);
+        });
+    }
+
+    #[test]
+    fn test_bad_write_address() {
+        let mut computer = IntcodeComputer::new(vec![40, -1, 99], vec![], false);
+        register_store(&mut computer, ParamRole::Write);
+        assert!(matches!(
+            computer.try_run(),
+            Err(IntcodeError::NegativeAddress {
+                index: 0,
+                param: 1,
+                ..
+            })
+        ));
+
+        let mut computer = IntcodeComputer::new(vec![40, 50, 99], vec![], false);
+        computer.set_memory_limit(10);
+        register_store(&mut computer, ParamRole::Write);
+        assert!(matches!(
+            computer.try_run(),
+            Err(IntcodeError::OutOfBounds { address: 50, .. })
+        ));
+        assert_eq!(computer.intcodes.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a write parameter")]
+    fn test_write_to_read_parameter() {
+        let mut computer = IntcodeComputer::new(vec![140, -1, 99], vec![], false);
+        register_store(&mut computer, ParamRole::Read);
+        computer.run();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_builtin_opcode() {
+        let mut computer = IntcodeComputer::new(vec![99], vec![], false);
+        computer.register_opcode(1, "add", vec![], |_| {});
+    }
+}
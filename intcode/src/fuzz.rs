@@ -0,0 +1,387 @@
+use crate::computer::{AddressingPolicy, IntcodeComputer, IntcodeOperation};
+use crate::parser::parse_intcodes;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+/// Values that tend to find edge cases in arithmetic and addressing
+const INTERESTING: [i128; 10] = [
+    i128::MAX,
+    i128::MIN,
+    i64::MAX as i128,
+    i64::MIN as i128,
+    u64::MAX as i128,
+    1 << 62,
+    -1,
+    -2,
+    usize::MAX as i128,
+    1 << 40,
+];
+
+/// A program and its inputs, stored in a corpus file as a `program:` and an
+/// `inputs:` line of comma-separated values
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FuzzCase {
+    pub intcodes: Vec<i128>,
+    pub inputs: Vec<i128>,
+}
+
+impl FuzzCase {
+    /// Parses a corpus file. Blank lines and lines starting with `#` are
+    /// skipped.
+    pub fn parse(contents: &str) -> Result<FuzzCase, String> {
+        let mut case = FuzzCase::default();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (field, values) = match line.find(':') {
+                Some(split) => (&line[..split], &line[split + 1..]),
+                None => return Err(format!("invalid line {}: {:?}", idx + 1, line)),
+            };
+            let values = match values.trim() {
+                // A case can have no inputs
+                "" => Vec::new(),
+                values => {
+                    parse_intcodes(values).map_err(|err| format!("line {}: {}", idx + 1, err))?
+                }
+            };
+            match field {
+                "program" => case.intcodes = values,
+                "inputs" => case.inputs = values,
+                _ => return Err(format!("unknown field on line {}: {:?}", idx + 1, field)),
+            }
+        }
+        Ok(case)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<FuzzCase, String> {
+        FuzzCase::parse(&fs::read_to_string(path).map_err(|err| err.to_string())?)
+    }
+
+    /// Saves the case in a corpus directory with a comment describing it, and
+    /// returns the file's path. The file name comes from a hash of the case,
+    /// so saving a case twice writes the same file.
+    pub fn save<P: AsRef<Path>>(&self, dir: P, comment: &str) -> io::Result<PathBuf> {
+        let contents = self.to_string();
+        let hash = contents
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+            });
+        fs::create_dir_all(&dir)?;
+        let path = dir.as_ref().join(format!("{:016x}.txt", hash));
+        let comments: String = comment
+            .lines()
+            .map(|line| format!("# {}\n", line))
+            .collect();
+        fs::write(&path, comments + &contents)?;
+        Ok(path)
+    }
+}
+
+fn write_field(f: &mut fmt::Formatter, name: &str, values: &[i128]) -> fmt::Result {
+    write!(f, "{}:", name)?;
+    for (idx, value) in values.iter().enumerate() {
+        write!(f, "{}{}", if idx == 0 { " " } else { "," }, value)?;
+    }
+    writeln!(f)
+}
+
+impl fmt::Display for FuzzCase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_field(f, "program", &self.intcodes)?;
+        write_field(f, "inputs", &self.inputs)
+    }
+}
+
+/// Limits on a single run of a case
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FuzzBudget {
+    pub steps: usize,
+    /// Memory limit in words
+    pub memory: usize,
+}
+
+impl Default for FuzzBudget {
+    fn default() -> FuzzBudget {
+        FuzzBudget {
+            steps: 10_000,
+            memory: 1 << 16,
+        }
+    }
+}
+
+/// Runs a case within the budget under both addressing policies. A case that
+/// stops within the budget is run again with `run`, or `try_run` if it
+/// faulted, which has to stop the same way. Halting, faulting and running out
+/// of steps are all fine, while a panic is a crash and returns its message.
+pub fn check_case(case: &FuzzCase, budget: FuzzBudget) -> Result<(), String> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let policies = [
+            AddressingPolicy::Lenient,
+            AddressingPolicy::Strict {
+                limit: budget.memory,
+            },
+        ];
+        for addressing in policies.iter() {
+            let spawn = || {
+                let mut computer =
+                    IntcodeComputer::new(case.intcodes.clone(), case.inputs.clone(), false);
+                computer.set_addressing(*addressing);
+                computer.set_memory_limit(budget.memory);
+                computer
+            };
+            let mut stepped = spawn();
+            let mut stopped = None;
+            for _ in 0..budget.steps {
+                match stepped.try_step() {
+                    Ok(Some(IntcodeOperation::Halt)) => {
+                        stopped = Some(Ok(IntcodeOperation::Halt));
+                        break;
+                    }
+                    Err(err) => {
+                        stopped = Some(Err(err));
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            // Only a case that stops in time can be run without a step limit
+            let stopped = match stopped {
+                Some(stopped) => stopped,
+                None => continue,
+            };
+            let mut computer = spawn();
+            let result = match stopped {
+                Ok(_) => Ok(computer.run()),
+                Err(_) => computer.try_run(),
+            };
+            if result != stopped
+                || computer.outputs != stepped.outputs
+                || computer.index != stepped.index
+            {
+                panic!(
+                    "running stopped with {:?}, but stepping stopped with {:?}",
+                    result, stopped
+                );
+            }
+        }
+    }));
+    result.map_err(|payload| {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "panic without a message".to_string()
+        }
+    })
+}
+
+/// Crash message without the numbers in it, so crashes from the same bug
+/// compare equal
+fn signature(message: &str) -> String {
+    message.chars().filter(|c| !c.is_ascii_digit()).collect()
+}
+
+/// Shrinks a case while `crashes` still holds for it, first by dropping runs
+/// of words and inputs, then by moving values closer to 0
+pub fn minimize<F: Fn(&FuzzCase) -> bool>(case: &FuzzCase, crashes: F) -> FuzzCase {
+    let mut best = case.clone();
+    loop {
+        let mut changed = false;
+        for inputs in [false, true].iter() {
+            let len = |case: &FuzzCase| match inputs {
+                true => case.inputs.len(),
+                false => case.intcodes.len(),
+            };
+            let mut chunk = len(&best).div_ceil(2);
+            while chunk > 0 {
+                let mut start = 0;
+                while start < len(&best) {
+                    let mut candidate = best.clone();
+                    let values = match inputs {
+                        true => &mut candidate.inputs,
+                        false => &mut candidate.intcodes,
+                    };
+                    values.drain(start..(start + chunk).min(values.len()));
+                    if crashes(&candidate) {
+                        best = candidate;
+                        changed = true;
+                    } else {
+                        start += chunk;
+                    }
+                }
+                chunk /= 2;
+            }
+        }
+
+        for idx in 0..best.intcodes.len() + best.inputs.len() {
+            let value = match idx.checked_sub(best.intcodes.len()) {
+                Some(input) => best.inputs[input],
+                None => best.intcodes[idx],
+            };
+            for simpler in [0, 1, -1, value / 2].iter() {
+                if simpler.unsigned_abs() >= value.unsigned_abs() {
+                    continue;
+                }
+                let mut candidate = best.clone();
+                match idx.checked_sub(best.intcodes.len()) {
+                    Some(input) => candidate.inputs[input] = *simpler,
+                    None => candidate.intcodes[idx] = *simpler,
+                }
+                if crashes(&candidate) {
+                    best = candidate;
+                    changed = true;
+                    break;
+                }
+            }
+        }
+        if !changed {
+            return best;
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Crash {
+    pub case: FuzzCase,
+    pub message: String,
+}
+
+/// Generates random programs and inputs and runs them through `check_case`
+pub struct Fuzzer {
+    /// xorshift state, never 0
+    state: u64,
+    budget: FuzzBudget,
+    max_len: usize,
+}
+
+impl Fuzzer {
+    pub fn new(seed: u64) -> Fuzzer {
+        Fuzzer {
+            state: seed.max(1),
+            budget: FuzzBudget::default(),
+            max_len: 32,
+        }
+    }
+
+    pub fn with_budget(mut self, budget: FuzzBudget) -> Fuzzer {
+        self.budget = budget;
+        self
+    }
+
+    /// Longest program to generate, in words
+    pub fn with_max_len(mut self, max_len: usize) -> Fuzzer {
+        self.max_len = max_len.max(1);
+        self
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// A word that's usually a plausible opcode, address or small value
+    fn word(&mut self) -> i128 {
+        match self.below(8) {
+            0..=2 => {
+                let opcodes = [1, 2, 3, 4, 5, 6, 7, 8, 9, 99];
+                let modes = match self.below(4) {
+                    0 => self.below(1000) as i128,
+                    _ => [0, 1, 2][self.below(3)] + [0, 10, 20][self.below(3)],
+                };
+                opcodes[self.below(opcodes.len())] + modes * 100
+            }
+            3..=5 => self.below(72) as i128 - 8,
+            6 => INTERESTING[self.below(INTERESTING.len())],
+            _ => self.next() as i64 as i128,
+        }
+    }
+
+    pub fn generate(&mut self) -> FuzzCase {
+        let len = 1 + self.below(self.max_len);
+        let inputs = self.below(5);
+        FuzzCase {
+            intcodes: (0..len).map(|_| self.word()).collect(),
+            inputs: (0..inputs).map(|_| self.word()).collect(),
+        }
+    }
+
+    /// Runs a number of random cases and returns one minimized case for each
+    /// distinct crash
+    pub fn run(&mut self, iterations: usize) -> Vec<Crash> {
+        let budget = self.budget;
+        let mut seen = BTreeSet::new();
+        let mut crashes = Vec::new();
+        for _ in 0..iterations {
+            let case = self.generate();
+            let message = match check_case(&case, budget) {
+                Ok(()) => continue,
+                Err(message) => message,
+            };
+            if !seen.insert(signature(&message)) {
+                continue;
+            }
+            let case = minimize(&case, |candidate| match check_case(candidate, budget) {
+                Err(other) => signature(&other) == signature(&message),
+                Ok(()) => false,
+            });
+            let message = check_case(&case, budget).unwrap_err();
+            crashes.push(Crash { case, message });
+        }
+        crashes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_minimize() {
+        // Stands in for a bug that needs a 7 in the program and an input over 5
+        let case = FuzzCase {
+            intcodes: vec![1, 2, 7, 3, 9, 99, 7],
+            inputs: vec![1, 8, 12],
+        };
+        let minimized = minimize(&case, |candidate| {
+            candidate.intcodes.contains(&7) && candidate.inputs.iter().any(|input| *input > 5)
+        });
+        assert_eq!(
+            minimized,
+            FuzzCase {
+                intcodes: vec![7],
+                inputs: vec![6],
+            }
+        );
+    }
+
+    #[test]
+    fn test_corpus_format() {
+        let case = FuzzCase {
+            intcodes: vec![1105, 1, -1],
+            inputs: vec![],
+        };
+        assert_eq!(case.to_string(), "program: 1105,1,-1\ninputs:\n");
+        assert_eq!(FuzzCase::parse(&format!("# jump\n{}", case)), Ok(case));
+        assert!(FuzzCase::parse("program 1,2").is_err());
+    }
+
+    #[test]
+    fn test_no_crashes() {
+        let crashes = Fuzzer::new(46).run(2000);
+        assert_eq!(crashes, vec![]);
+    }
+}
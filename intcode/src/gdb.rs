@@ -0,0 +1,518 @@
+use crate::computer::{IntcodeComputer, IntcodeOperation};
+use crate::memory::WORD_BYTES;
+use std::collections::BTreeSet;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// Steps to run between checks for an interrupt from the client
+const POLL_STEPS: usize = 10_000;
+/// Largest memory read a client can ask for in one packet
+const MAX_READ: usize = 4096;
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.intcode.core">
+    <reg name="ip" bitsize="64" type="code_ptr" regnum="0"/>
+    <reg name="rb" bitsize="64" type="data_ptr" regnum="1"/>
+  </feature>
+</target>
+"#;
+
+/// Serves a machine to a debugger over the GDB Remote Serial Protocol.
+///
+/// Memory is shown as 64-bit little-endian words, 8 bytes per Intcode word, so
+/// byte address `8 * n` holds word `n` and `x/4gd 0` prints the first four
+/// words. Words wider than 64 bits are truncated when read. The two registers
+/// are `ip` and `rb`, both as byte addresses. Outputs are sent to the client's
+/// console as the program produces them.
+pub struct GdbStub {
+    pub computer: IntcodeComputer,
+    breakpoints: BTreeSet<usize>,
+    no_ack: bool,
+    /// Whether the client understands `swbreak` in stop replies
+    swbreak: bool,
+    /// Outputs already sent to the client
+    reported: usize,
+}
+
+impl GdbStub {
+    pub fn new(computer: IntcodeComputer) -> GdbStub {
+        GdbStub {
+            computer,
+            breakpoints: BTreeSet::new(),
+            no_ack: false,
+            swbreak: false,
+            reported: 0,
+        }
+    }
+
+    /// Accepts a single client and serves it until it detaches, kills the
+    /// program or disconnects
+    pub fn serve(&mut self, listener: &TcpListener) -> io::Result<()> {
+        let (stream, _) = listener.accept()?;
+        self.serve_stream(stream)
+    }
+
+    pub fn serve_stream(&mut self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+        loop {
+            let packet = match read_packet(&mut stream)? {
+                Some(Packet::Data(packet)) => packet,
+                Some(Packet::Interrupt) => continue,
+                Some(Packet::Invalid) => {
+                    stream.write_all(b"-")?;
+                    continue;
+                }
+                None => return Ok(()),
+            };
+            if !self.no_ack {
+                stream.write_all(b"+")?;
+            }
+            let reply = match packet.chars().next() {
+                Some('c') => self.resume(&mut stream, false)?,
+                Some('s') => self.resume(&mut stream, true)?,
+                Some('k') => return Ok(()),
+                Some('D') => {
+                    write_packet(&mut stream, "OK")?;
+                    return Ok(());
+                }
+                _ => self.command(&packet),
+            };
+            write_packet(&mut stream, &reply)?;
+        }
+    }
+
+    /// Replies to every packet that doesn't run the program
+    fn command(&mut self, packet: &str) -> String {
+        if packet == "QStartNoAckMode" {
+            self.no_ack = true;
+            return "OK".to_string();
+        }
+        if packet.is_empty() || !packet.is_char_boundary(1) {
+            return String::new();
+        }
+        let (name, args) = packet.split_at(1);
+        match name {
+            "?" => "S05".to_string(),
+            "g" => format!(
+                "{}{}",
+                hex_word(self.computer.index as i128 * WORD_BYTES as i128),
+                hex_word(self.computer.relative_base * WORD_BYTES as i128)
+            ),
+            "G" => match (parse_word(args.get(..16)), parse_word(args.get(16..32))) {
+                (Some(ip), Some(rb)) => {
+                    self.set_register(0, ip);
+                    self.set_register(1, rb);
+                    "OK".to_string()
+                }
+                _ => "E01".to_string(),
+            },
+            "p" => match usize::from_str_radix(args, 16) {
+                Ok(0) => hex_word(self.computer.index as i128 * WORD_BYTES as i128),
+                Ok(1) => hex_word(self.computer.relative_base * WORD_BYTES as i128),
+                _ => "E01".to_string(),
+            },
+            "P" => {
+                let parts: Vec<&str> = args.splitn(2, '=').collect();
+                match (
+                    parts
+                        .first()
+                        .and_then(|n| usize::from_str_radix(n, 16).ok()),
+                    parse_word(parts.get(1).copied()),
+                ) {
+                    (Some(reg), Some(value)) if reg < 2 => {
+                        self.set_register(reg, value);
+                        "OK".to_string()
+                    }
+                    _ => "E01".to_string(),
+                }
+            }
+            "m" => {
+                let limit = self.computer.limit();
+                let bytes = parse_range(args).and_then(|(address, len)| {
+                    self.computer
+                        .intcodes
+                        .read_bytes(address, len.min(MAX_READ), limit)
+                });
+                match bytes {
+                    Some(bytes) => encode_hex(&bytes),
+                    None => "E01".to_string(),
+                }
+            }
+            "M" => {
+                let parts: Vec<&str> = args.splitn(2, ':').collect();
+                match (parts.first().and_then(|r| parse_range(r)), parts.get(1)) {
+                    (Some((address, len)), Some(data)) => {
+                        let limit = self.computer.limit();
+                        let written = decode_hex(data)
+                            .filter(|bytes| bytes.len() == len)
+                            .and_then(|bytes| {
+                                self.computer.intcodes.write_bytes(address, &bytes, limit)
+                            });
+                        match written {
+                            Some(()) => "OK".to_string(),
+                            None => "E01".to_string(),
+                        }
+                    }
+                    _ => "E01".to_string(),
+                }
+            }
+            "Z" | "z" => {
+                let parts: Vec<&str> = args.split(',').collect();
+                let address = parts.get(1).and_then(|a| usize::from_str_radix(a, 16).ok());
+                match (parts.first(), address) {
+                    // Hardware breakpoints work the same as software ones
+                    (Some(&"0"), Some(address)) | (Some(&"1"), Some(address)) => {
+                        if name == "Z" {
+                            self.breakpoints.insert(address / WORD_BYTES);
+                        } else {
+                            self.breakpoints.remove(&(address / WORD_BYTES));
+                        }
+                        "OK".to_string()
+                    }
+                    _ => String::new(),
+                }
+            }
+            "H" => "OK".to_string(),
+            "q" => self.query(packet),
+            _ => String::new(),
+        }
+    }
+
+    fn query(&mut self, packet: &str) -> String {
+        if let Some(features) = packet.strip_prefix("qSupported") {
+            self.swbreak = features
+                .trim_start_matches(':')
+                .split(';')
+                .any(|feature| feature == "swbreak+");
+            return "PacketSize=4000;swbreak+;qXfer:features:read+;QStartNoAckMode+".to_string();
+        }
+        if let Some(range) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            return match parse_range(range) {
+                Some((offset, len)) => {
+                    let chunk = TARGET_XML.get(offset..).unwrap_or("");
+                    if chunk.len() > len {
+                        format!("m{}", &chunk[..len])
+                    } else {
+                        format!("l{}", chunk)
+                    }
+                }
+                None => "E01".to_string(),
+            };
+        }
+        match packet {
+            "qAttached" => "1".to_string(),
+            "qC" => "QC1".to_string(),
+            "qfThreadInfo" => "m1".to_string(),
+            "qsThreadInfo" => "l".to_string(),
+            "qSymbol::" => "OK".to_string(),
+            "qHostInfo" => "ptrsize:8;endian:little;".to_string(),
+            "qRegisterInfo0" => "name:ip;bitsize:64;offset:0;encoding:uint;format:hex;\
+                                 set:General Purpose Registers;generic:pc;"
+                .to_string(),
+            "qRegisterInfo1" => "name:rb;bitsize:64;offset:8;encoding:uint;format:hex;\
+                                 set:General Purpose Registers;generic:fp;"
+                .to_string(),
+            _ if packet.starts_with("qRegisterInfo") => "E45".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn set_register(&mut self, reg: usize, value: i128) {
+        match reg {
+            0 => self.computer.index = (value / WORD_BYTES as i128).max(0) as usize,
+            _ => self.computer.relative_base = value / WORD_BYTES as i128,
+        }
+    }
+
+    /// Runs one instruction, or until a breakpoint, halt or interrupt, and
+    /// returns the stop reply. Addressing faults stop with SIGSEGV, and
+    /// breakpoints are reported as `swbreak` to clients that support it.
+    fn resume(&mut self, stream: &mut TcpStream, single: bool) -> io::Result<String> {
+        let mut reply = "S05";
+        let mut steps: usize = 0;
+        loop {
+            match self.computer.try_step() {
+                Ok(Some(IntcodeOperation::Halt)) => reply = "W00",
+                Err(_) => reply = "S0b",
+                _ => {}
+            }
+            // An input with nothing queued takes back the last output, which
+            // may have been sent already
+            self.reported = self.reported.min(self.computer.outputs.len());
+            if reply != "S05" {
+                break;
+            }
+            steps += 1;
+            if single {
+                break;
+            }
+            if self.breakpoints.contains(&self.computer.index) {
+                if self.swbreak {
+                    reply = "T05swbreak:;";
+                }
+                break;
+            }
+            if steps.is_multiple_of(POLL_STEPS) && interrupted(stream)? {
+                break;
+            }
+        }
+        self.report_outputs(stream)?;
+        Ok(reply.to_string())
+    }
+
+    /// Sends new outputs to the client's console
+    fn report_outputs(&mut self, stream: &mut TcpStream) -> io::Result<()> {
+        let start = self.reported.min(self.computer.outputs.len());
+        for value in self.computer.outputs[start..].iter() {
+            let text = format!("{}\n", value);
+            write_packet(stream, &format!("O{}", encode_hex(text.as_bytes())))?;
+        }
+        self.reported = self.computer.outputs.len();
+        Ok(())
+    }
+}
+
+enum Packet {
+    Data(String),
+    /// Ctrl-C sent outside of a packet
+    Interrupt,
+    /// A packet with a bad checksum
+    Invalid,
+}
+
+fn read_byte(stream: &mut TcpStream) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    match stream.read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+fn read_packet(stream: &mut TcpStream) -> io::Result<Option<Packet>> {
+    loop {
+        match read_byte(stream)? {
+            None => return Ok(None),
+            Some(0x03) => return Ok(Some(Packet::Interrupt)),
+            Some(b'$') => break,
+            // Acks and anything else between packets
+            Some(_) => {}
+        }
+    }
+    let mut data = Vec::new();
+    loop {
+        match read_byte(stream)? {
+            None => return Ok(None),
+            Some(b'#') => break,
+            Some(byte) => data.push(byte),
+        }
+    }
+    let mut checksum = [0; 2];
+    stream.read_exact(&mut checksum)?;
+    let expected = std::str::from_utf8(&checksum)
+        .ok()
+        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    let actual = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+    if expected != Some(actual) {
+        return Ok(Some(Packet::Invalid));
+    }
+    Ok(Some(Packet::Data(
+        String::from_utf8_lossy(&data).into_owned(),
+    )))
+}
+
+fn write_packet(stream: &mut TcpStream, data: &str) -> io::Result<()> {
+    let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+    write!(stream, "${}#{:02x}", data, checksum)
+}
+
+/// Whether the client sent a Ctrl-C while the program was running
+fn interrupted(stream: &mut TcpStream) -> io::Result<bool> {
+    stream.set_nonblocking(true)?;
+    let mut byte = [0];
+    let result = match stream.peek(&mut byte) {
+        Ok(1) if byte[0] == 0x03 => {
+            stream.read_exact(&mut byte)?;
+            Ok(true)
+        }
+        Ok(_) => Ok(false),
+        Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(false),
+        Err(err) => Err(err),
+    };
+    stream.set_nonblocking(false)?;
+    result
+}
+
+fn hex_word(value: i128) -> String {
+    encode_hex(&(value as i64).to_le_bytes())
+}
+
+fn parse_word(hex: Option<&str>) -> Option<i128> {
+    let bytes = decode_hex(hex?)?;
+    let mut word = [0; 8];
+    word.get_mut(..bytes.len())?.copy_from_slice(&bytes);
+    Some(i64::from_le_bytes(word) as i128)
+}
+
+/// Parses an `address,length` pair in hex
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    let mut parts = range.split(',');
+    let address = usize::from_str_radix(parts.next()?, 16).ok()?;
+    let len = usize::from_str_radix(parts.next()?, 16).ok()?;
+    Some((address, len))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    /// Sends a packet and returns the replies up to the next non-console one
+    fn request(stream: &mut TcpStream, data: &str) -> (String, Vec<String>) {
+        write_packet(stream, data).unwrap();
+        let mut console = Vec::new();
+        loop {
+            match read_packet(stream).unwrap() {
+                Some(Packet::Data(reply)) if reply.starts_with('O') && reply != "OK" => {
+                    let text = decode_hex(&reply[1..]).unwrap();
+                    console.push(String::from_utf8(text).unwrap());
+                }
+                Some(Packet::Data(reply)) => return (reply, console),
+                _ => panic!("no reply to {}", data),
+            }
+        }
+    }
+
+    fn connect(intcodes: Vec<i128>) -> (TcpStream, thread::JoinHandle<IntcodeComputer>) {
+        connect_computer(IntcodeComputer::new(intcodes, vec![], false))
+    }
+
+    fn connect_computer(
+        computer: IntcodeComputer,
+    ) -> (TcpStream, thread::JoinHandle<IntcodeComputer>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let mut stub = GdbStub::new(computer);
+            stub.serve(&listener).unwrap();
+            stub.computer
+        });
+        let client = TcpStream::connect(address).unwrap();
+        client.set_nodelay(true).unwrap();
+        (client, server)
+    }
+
+    #[test]
+    fn test_registers_and_memory() {
+        let (mut client, server) = connect(vec![109, 19, 204, -1, 99]);
+        assert_eq!(request(&mut client, "?").0, "S05");
+        assert_eq!(request(&mut client, "s").0, "S05");
+        // ip is at word 2 and rb is 19, both as byte addresses
+        assert_eq!(
+            request(&mut client, "g").0,
+            "10000000000000009800000000000000"
+        );
+        assert_eq!(request(&mut client, "p1").0, "9800000000000000");
+
+        assert_eq!(
+            request(&mut client, "m8,10").0,
+            "1300000000000000cc00000000000000"
+        );
+        // Word 3 as a negative number
+        assert_eq!(request(&mut client, "m18,8").0, "ffffffffffffffff");
+        // Make the output read word 18 after writing 42 there
+        assert_eq!(request(&mut client, "M90,8:2a00000000000000").0, "OK");
+        let (reply, console) = request(&mut client, "c");
+        assert_eq!(reply, "W00");
+        assert_eq!(console, vec!["42\n"]);
+        request(&mut client, "D");
+        assert_eq!(server.join().unwrap().outputs, vec![42]);
+    }
+
+    #[test]
+    fn test_memory_bounds() {
+        let mut computer = IntcodeComputer::new(vec![99], vec![], false);
+        computer.set_memory_limit(4);
+        let (mut client, server) = connect_computer(computer);
+        assert_eq!(request(&mut client, "mffffffffffffffff,10").0, "E01");
+        // Words past the end read as 0 up to the limit
+        assert_eq!(request(&mut client, "m18,8").0, "0000000000000000");
+        assert_eq!(request(&mut client, "m18,9").0, "E01");
+        assert_eq!(request(&mut client, "M1c,8:0100000000000000").0, "E01");
+        assert_eq!(request(&mut client, "M18,8:0100000000000000").0, "OK");
+        request(&mut client, "D");
+        assert_eq!(server.join().unwrap().intcodes.to_vec(), vec![99, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_swbreak_stop_reason() {
+        let (mut client, server) = connect(vec![104, 1, 104, 2, 99]);
+        request(&mut client, "qSupported:multiprocess+;swbreak+");
+        assert_eq!(request(&mut client, "Z0,10,1").0, "OK");
+        assert_eq!(request(&mut client, "c").0, "T05swbreak:;");
+        // Stepping isn't a breakpoint hit
+        assert_eq!(request(&mut client, "s").0, "S05");
+        write_packet(&mut client, "k").unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        // Outputs 1, 2 and 3 and halts
+        let (mut client, server) = connect(vec![104, 1, 104, 2, 104, 3, 99]);
+        write!(client, "+").unwrap();
+        assert_eq!(request(&mut client, "Z0,20,1").0, "OK");
+        let (reply, console) = request(&mut client, "c");
+        assert_eq!(reply, "S05");
+        assert_eq!(console, vec!["1\n", "2\n"]);
+        assert_eq!(request(&mut client, "p0").0, "2000000000000000");
+
+        assert_eq!(request(&mut client, "z0,20,1").0, "OK");
+        assert_eq!(request(&mut client, "Z0,10,1").0, "OK");
+        let (reply, console) = request(&mut client, "c");
+        assert_eq!((reply.as_str(), console), ("W00", vec!["3\n".to_string()]));
+        write_packet(&mut client, "k").unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_input_takes_back_reported_output() {
+        // Reads its own output back as the input and outputs again
+        let (mut client, server) = connect(vec![104, 7, 3, 9, 4, 9, 99, 0, 0, 0]);
+        assert_eq!(request(&mut client, "Z0,10,1").0, "OK");
+        let (reply, console) = request(&mut client, "c");
+        assert_eq!((reply.as_str(), console), ("S05", vec!["7\n".to_string()]));
+        let (reply, console) = request(&mut client, "c");
+        assert_eq!((reply.as_str(), console), ("W00", vec!["7\n".to_string()]));
+        write_packet(&mut client, "k").unwrap();
+        assert_eq!(server.join().unwrap().outputs, vec![7]);
+    }
+
+    #[test]
+    fn test_target_description() {
+        let (mut client, server) = connect(vec![99]);
+        assert!(request(&mut client, "qSupported:swbreak+")
+            .0
+            .contains("qXfer:features:read+"));
+        let (reply, _) = request(&mut client, "qXfer:features:read:target.xml:0,fff");
+        assert!(reply.starts_with('l') && reply.contains("name=\"rb\""));
+        assert_eq!(request(&mut client, "vMustReplyEmpty").0, "");
+        write_packet(&mut client, "k").unwrap();
+        server.join().unwrap();
+    }
+}
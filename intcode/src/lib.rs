@@ -0,0 +1,48 @@
+mod amplifier;
+mod ascii;
+mod binary;
+mod computer;
+mod dap;
+mod decompile;
+mod diff;
+mod driver;
+mod error;
+mod events;
+mod extension;
+mod fuzz;
+mod gdb;
+mod memory;
+mod observer;
+mod optimize;
+mod parser;
+mod program;
+mod replay;
+mod search;
+mod symbolic;
+mod transpile;
+
+pub use amplifier::{run_amplifiers, AmplifierWiring, PhaseObjective, PhaseResult, PhaseSearch};
+pub use ascii::AsciiAdapter;
+pub use binary::{format_symbols, parse_symbols, BinaryError, ProgramContainer};
+pub use computer::{
+    AddressingPolicy, IntcodeComputer, IntcodeInstruction, IntcodeMode, IntcodeOperation,
+    DEFAULT_MEMORY_LIMIT,
+};
+pub use dap::DapServer;
+pub use decompile::Decompiler;
+pub use diff::{CellChange, ChangedRange, CodeCell, MemoryDiff};
+pub use driver::{channel, AsyncInput, AsyncOutput, Executor, Receiver, Sender};
+pub use error::IntcodeError;
+pub use events::{OutputEvent, OutputStream, Outputs};
+pub use extension::{CustomContext, CustomOpcode, OpcodeRegistry, ParamRole};
+pub use fuzz::{check_case, minimize, Crash, FuzzBudget, FuzzCase, Fuzzer};
+pub use gdb::GdbStub;
+pub use memory::Memory;
+pub use observer::{IntcodeObserver, ObserverSlot};
+pub use optimize::{optimize, Optimized};
+pub use parser::{parse_intcodes, ParseError};
+pub use program::Program;
+pub use replay::{InputRecorder, InputSlot, InputSource, Recording, Replayer, StdinInput};
+pub use search::ParameterSearch;
+pub use symbolic::{Polynomial, SymbolicSolver};
+pub use transpile::transpile;
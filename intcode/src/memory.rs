@@ -0,0 +1,159 @@
+use std::ops::{Index, IndexMut};
+use std::sync::Arc;
+
+const PAGE_SIZE: usize = 256;
+/// Bytes per word when memory is viewed as bytes by a debugger
+pub(crate) const WORD_BYTES: usize = 8;
+
+/// Intcode memory split into shared pages. Cloning only copies page pointers,
+/// and a page is copied the first time it's written to, so many machines can
+/// run off of one program image without each holding a full copy.
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    pages: Vec<Arc<Vec<i128>>>,
+    len: usize,
+}
+
+impl Memory {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, address: usize) -> Option<&i128> {
+        if address < self.len {
+            Some(&self.pages[address / PAGE_SIZE][address % PAGE_SIZE])
+        } else {
+            None
+        }
+    }
+
+    /// Grows memory with zeros until it's at least `len` long
+    pub fn extend(&mut self, len: usize) {
+        while self.len < len {
+            if self.len.is_multiple_of(PAGE_SIZE) {
+                self.pages.push(Arc::new(Vec::with_capacity(PAGE_SIZE)));
+            }
+            let page = Arc::make_mut(self.pages.last_mut().unwrap());
+            let fill = (PAGE_SIZE - page.len()).min(len - self.len);
+            page.resize(page.len() + fill, 0);
+            self.len += fill;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &i128> {
+        self.pages.iter().flat_map(|page| page.iter())
+    }
+
+    pub fn to_vec(&self) -> Vec<i128> {
+        self.iter().copied().collect()
+    }
+
+    /// Reads memory as 64-bit little-endian words, so byte `8 * n` starts word
+    /// `n`. Words wider than 64 bits are truncated and anything past the end
+    /// reads as 0. Returns `None` if the range reaches past word `limit`.
+    pub(crate) fn read_bytes(&self, address: usize, len: usize, limit: usize) -> Option<Vec<u8>> {
+        let first = address / WORD_BYTES;
+        let last = byte_range_end(address, len, limit)?;
+        let bytes: Vec<u8> = (first..last)
+            .flat_map(|idx| (*self.get(idx).unwrap_or(&0) as i64).to_le_bytes())
+            .collect();
+        let start = address - first * WORD_BYTES;
+        Some(bytes[start..start + len].to_vec())
+    }
+
+    /// Writes bytes in the same layout as `read_bytes`, sign extending each
+    /// word that's changed. Returns `None` without writing anything if memory
+    /// would have to grow past word `limit`.
+    pub(crate) fn write_bytes(&mut self, address: usize, data: &[u8], limit: usize) -> Option<()> {
+        let first = address / WORD_BYTES;
+        let last = byte_range_end(address, data.len(), limit)?;
+        let mut bytes = self.read_bytes(first * WORD_BYTES, (last - first) * WORD_BYTES, limit)?;
+        let start = address - first * WORD_BYTES;
+        bytes[start..start + data.len()].copy_from_slice(data);
+        self.extend(last);
+        for (idx, chunk) in bytes.chunks(WORD_BYTES).enumerate() {
+            let mut word = [0; WORD_BYTES];
+            word.copy_from_slice(chunk);
+            self[first + idx] = i64::from_le_bytes(word) as i128;
+        }
+        Some(())
+    }
+
+    /// Number of pages shared with another `Memory`, mostly useful to check
+    /// how much copying has happened
+    pub fn shared_pages(&self, other: &Memory) -> usize {
+        self.pages
+            .iter()
+            .zip(other.pages.iter())
+            .filter(|(a, b)| Arc::ptr_eq(a, b))
+            .count()
+    }
+}
+
+/// Word just past a byte range, if it's within `limit` words
+fn byte_range_end(address: usize, len: usize, limit: usize) -> Option<usize> {
+    let end = address.checked_add(len)?.div_ceil(WORD_BYTES);
+    if end > limit {
+        return None;
+    }
+    Some(end)
+}
+
+impl From<Vec<i128>> for Memory {
+    fn from(intcodes: Vec<i128>) -> Memory {
+        Memory {
+            len: intcodes.len(),
+            pages: intcodes
+                .chunks(PAGE_SIZE)
+                .map(|chunk| Arc::new(chunk.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+impl From<&[i128]> for Memory {
+    fn from(intcodes: &[i128]) -> Memory {
+        Memory::from(intcodes.to_vec())
+    }
+}
+
+impl Index<usize> for Memory {
+    type Output = i128;
+
+    fn index(&self, address: usize) -> &i128 {
+        self.get(address).unwrap_or_else(|| {
+            panic!(
+                "address {} out of bounds for memory of length {}",
+                address, self.len
+            )
+        })
+    }
+}
+
+impl IndexMut<usize> for Memory {
+    fn index_mut(&mut self, address: usize) -> &mut i128 {
+        if address >= self.len {
+            panic!(
+                "address {} out of bounds for memory of length {}",
+                address, self.len
+            );
+        }
+        &mut Arc::make_mut(&mut self.pages[address / PAGE_SIZE])[address % PAGE_SIZE]
+    }
+}
+
+impl PartialEq for Memory {
+    fn eq(&self, other: &Memory) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl PartialEq<Vec<i128>> for Memory {
+    fn eq(&self, other: &Vec<i128>) -> bool {
+        self.len == other.len() && self.iter().eq(other.iter())
+    }
+}
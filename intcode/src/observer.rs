@@ -0,0 +1,137 @@
+use crate::computer::{IntcodeComputer, IntcodeInstruction};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Callbacks for events while a machine runs. Every method does nothing by
+/// default, so observers only implement what they need.
+///
+/// Custom opcodes don't decode to an `IntcodeInstruction`, so they aren't
+/// reported to `before_instruction` or `after_instruction`, but their memory
+/// writes are.
+pub trait IntcodeObserver: Send {
+    fn before_instruction(&mut self, _index: usize, _instruction: &IntcodeInstruction) {}
+    fn after_instruction(&mut self, _index: usize, _instruction: &IntcodeInstruction) {}
+    fn on_memory_write(&mut self, _address: usize, _old: i128, _new: i128) {}
+    fn on_input(&mut self, _value: i128) {}
+    fn on_output(&mut self, _value: i128) {}
+    fn on_relative_base_change(&mut self, _old: i128, _new: i128) {}
+    fn on_halt(&mut self, _index: usize) {}
+}
+
+/// Holds the observer attached to a machine, if any. Clones of a machine share
+/// the same observer.
+#[derive(Clone, Default)]
+pub struct ObserverSlot(Option<Arc<Mutex<dyn IntcodeObserver>>>);
+
+impl ObserverSlot {
+    /// Calls `f` on the attached observer. With nothing attached this is just
+    /// a branch, so unobserved machines don't pay for observers.
+    #[inline]
+    pub(crate) fn notify<F: FnOnce(&mut dyn IntcodeObserver)>(&self, f: F) {
+        if let Some(observer) = &self.0 {
+            f(&mut *observer.lock().unwrap());
+        }
+    }
+}
+
+impl fmt::Debug for ObserverSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "ObserverSlot(attached)"),
+            None => write!(f, "ObserverSlot(none)"),
+        }
+    }
+}
+
+impl IntcodeComputer {
+    /// Attaches an observer, replacing any that's already attached. Keep a
+    /// clone of the `Arc` to read the observer's state back afterwards.
+    pub fn attach_observer(&mut self, observer: Arc<Mutex<dyn IntcodeObserver>>) {
+        self.observer = ObserverSlot(Some(observer));
+    }
+
+    pub fn detach_observer(&mut self) -> Option<Arc<Mutex<dyn IntcodeObserver>>> {
+        self.observer.0.take()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::computer::IntcodeOperation;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl IntcodeObserver for Recorder {
+        fn before_instruction(&mut self, index: usize, instruction: &IntcodeInstruction) {
+            self.events
+                .push(format!("before {} {:?}", index, instruction.operation));
+        }
+
+        fn after_instruction(&mut self, index: usize, _instruction: &IntcodeInstruction) {
+            self.events.push(format!("after {}", index));
+        }
+
+        fn on_memory_write(&mut self, address: usize, old: i128, new: i128) {
+            self.events
+                .push(format!("write {} {} -> {}", address, old, new));
+        }
+
+        fn on_input(&mut self, value: i128) {
+            self.events.push(format!("input {}", value));
+        }
+
+        fn on_output(&mut self, value: i128) {
+            self.events.push(format!("output {}", value));
+        }
+
+        fn on_relative_base_change(&mut self, old: i128, new: i128) {
+            self.events.push(format!("base {} -> {}", old, new));
+        }
+
+        fn on_halt(&mut self, index: usize) {
+            self.events.push(format!("halt {}", index));
+        }
+    }
+
+    #[test]
+    fn test_observer_events() {
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let mut computer =
+            IntcodeComputer::new(vec![3, 9, 109, 4, 204, 5, 99, 0, 0, 0], vec![7], false);
+        computer.attach_observer(recorder.clone());
+        assert_eq!(computer.run(), IntcodeOperation::Halt);
+        assert_eq!(
+            recorder.lock().unwrap().events,
+            vec![
+                "before 0 Input",
+                "input 7",
+                "write 9 0 -> 7",
+                "after 0",
+                "before 2 RelativeBaseOffset",
+                "base 0 -> 4",
+                "after 2",
+                "before 4 Output",
+                "output 7",
+                "after 4",
+                "before 6 Halt",
+                "halt 6",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detach_observer() {
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let mut computer = IntcodeComputer::new(vec![104, 1, 104, 2, 99], vec![], true);
+        computer.attach_observer(recorder.clone());
+        computer.run();
+        assert!(computer.detach_observer().is_some());
+        computer.run();
+        assert_eq!(computer.outputs, vec![1, 2]);
+        assert_eq!(recorder.lock().unwrap().events.len(), 3);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::computer::{instruction_len, IntcodeInstruction, IntcodeMode, IntcodeOperation};
+use crate::program::Program;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Optimized program along with where each of the original addresses ended up
+#[derive(Clone, Debug, PartialEq)]
+pub struct Optimized {
+    pub program: Program,
+    /// New address for each address in the original image. Removed words map
+    /// to the next word that was kept.
+    pub addresses: Vec<usize>,
+}
+
+impl Optimized {
+    /// New address for an original address, including ones past the end of
+    /// the image that the program only reaches by extending memory
+    pub fn address(&self, old: usize) -> usize {
+        match self.addresses.get(old) {
+            Some(new) => *new,
+            None => old + self.program.len() - self.addresses.len(),
+        }
+    }
+}
+
+/// What the program can do, found by walking every reachable instruction
+struct Analysis {
+    instructions: BTreeMap<usize, IntcodeInstruction>,
+    written: BTreeSet<usize>,
+    /// Addresses read as data by position mode parameters
+    read: BTreeSet<usize>,
+    /// Parameter cells holding an address, which move when code is removed
+    address_params: BTreeSet<usize>,
+    /// Whether code can be removed and everything after it moved up. Only
+    /// true when no jump target is read from data at runtime.
+    relocatable: bool,
+}
+
+impl Analysis {
+    /// Whether nothing in the instruction at `address` is ever written or
+    /// read as data, so it can be rewritten
+    fn is_fixed(&self, address: usize) -> bool {
+        let len = instruction_len(self.instructions[&address].operation);
+        (address..address + len)
+            .all(|cell| !self.written.contains(&cell) && !self.read.contains(&cell))
+    }
+}
+
+/// Parameter roles for each operation, with true for the parameter written to
+fn params(operation: IntcodeOperation) -> &'static [bool] {
+    match operation {
+        IntcodeOperation::Add
+        | IntcodeOperation::Multiply
+        | IntcodeOperation::LessThan
+        | IntcodeOperation::Equal => &[false, false, true],
+        IntcodeOperation::Input => &[true],
+        IntcodeOperation::Output
+        | IntcodeOperation::JumpIfTrue
+        | IntcodeOperation::JumpIfFalse
+        | IntcodeOperation::RelativeBaseOffset => &[false],
+        IntcodeOperation::Halt => &[],
+    }
+}
+
+fn mode(instruction: &IntcodeInstruction, n: usize) -> IntcodeMode {
+    match n {
+        1 => instruction.mode_1,
+        2 => instruction.mode_2,
+        _ => instruction.mode_3,
+    }
+}
+
+/// Works out every address the program can execute, write and read. Returns
+/// `None` if that can't be known statically: relative mode parameters, writes
+/// to opcodes or to parameters holding addresses, or instructions that overlap
+/// or run past the end of the image.
+fn analyze(image: &[i128]) -> Option<Analysis> {
+    let word = |address: usize| *image.get(address).unwrap_or(&0);
+    let mut analysis = Analysis {
+        instructions: BTreeMap::new(),
+        written: BTreeSet::new(),
+        read: BTreeSet::new(),
+        address_params: BTreeSet::new(),
+        relocatable: true,
+    };
+    // Cells jump targets were read from, which have to stay constant
+    let mut target_cells = BTreeSet::new();
+    let mut pending = vec![0];
+
+    while let Some(address) = pending.pop() {
+        if analysis.instructions.contains_key(&address) {
+            continue;
+        }
+        let instruction = IntcodeInstruction::try_from_num(word(address))?;
+        analysis.instructions.insert(address, instruction);
+
+        for (idx, is_write) in params(instruction.operation).iter().enumerate() {
+            let cell = address + idx + 1;
+            match mode(&instruction, idx + 1) {
+                IntcodeMode::Relative => return None,
+                IntcodeMode::Immediate => {}
+                IntcodeMode::Position if word(cell) < 0 => {
+                    if *is_write {
+                        return None;
+                    }
+                }
+                IntcodeMode::Position => {
+                    analysis.address_params.insert(cell);
+                    if *is_write {
+                        analysis.written.insert(word(cell) as usize);
+                    } else {
+                        analysis.read.insert(word(cell) as usize);
+                    }
+                }
+            }
+        }
+
+        match instruction.operation {
+            IntcodeOperation::Halt => {}
+            IntcodeOperation::JumpIfTrue | IntcodeOperation::JumpIfFalse => {
+                pending.push(address + 3);
+                let cell = address + 2;
+                let target = match instruction.mode_2 {
+                    IntcodeMode::Immediate => word(cell),
+                    IntcodeMode::Position if word(cell) >= 0 => {
+                        analysis.read.insert(word(cell) as usize);
+                        analysis.relocatable = false;
+                        target_cells.insert(word(cell) as usize);
+                        word(word(cell) as usize)
+                    }
+                    _ => return None,
+                };
+                analysis.address_params.insert(cell);
+                target_cells.insert(cell);
+                // A negative target panics in the interpreter, so leave the
+                // program alone
+                if target < 0 {
+                    return None;
+                }
+                pending.push(target as usize);
+            }
+            op => pending.push(address + instruction_len(op)),
+        }
+    }
+
+    let mut cells = BTreeSet::new();
+    for (address, instruction) in analysis.instructions.iter() {
+        for cell in *address..*address + instruction_len(instruction.operation) {
+            if cell >= image.len() || !cells.insert(cell) {
+                return None;
+            }
+        }
+    }
+    let fixed = |cell: &usize| {
+        analysis.instructions.contains_key(cell)
+            || analysis.address_params.contains(cell)
+            || target_cells.contains(cell)
+    };
+    if analysis.written.iter().any(fixed) {
+        return None;
+    }
+    // Moving code changes address parameters, so they can't be read as data
+    if analysis
+        .address_params
+        .iter()
+        .any(|cell| analysis.read.contains(cell))
+    {
+        analysis.relocatable = false;
+    }
+    Some(analysis)
+}
+
+/// Whether a jump's condition is an immediate constant, and if so whether it's
+/// always taken
+fn constant_jump(words: &[i128], address: usize, instruction: &IntcodeInstruction) -> Option<bool> {
+    let taken_if_nonzero = match instruction.operation {
+        IntcodeOperation::JumpIfTrue => true,
+        IntcodeOperation::JumpIfFalse => false,
+        _ => return None,
+    };
+    if instruction.mode_1 != IntcodeMode::Immediate {
+        return None;
+    }
+    Some((words[address + 1] != 0) == taken_if_nonzero)
+}
+
+/// Follows a jump target through unconditional jumps and jumps that are never
+/// taken to where execution actually continues
+fn thread(words: &[i128], analysis: &Analysis, mut target: usize) -> usize {
+    let mut seen = BTreeSet::new();
+    while seen.insert(target) {
+        if !analysis.instructions.contains_key(&target) || !analysis.is_fixed(target) {
+            break;
+        }
+        // Decoded again since the jump may already have been rewritten
+        let instruction = match IntcodeInstruction::try_from_num(words[target]) {
+            Some(instruction) => instruction,
+            None => break,
+        };
+        match constant_jump(words, target, &instruction) {
+            Some(true) if instruction.mode_2 == IntcodeMode::Immediate => {
+                target = words[target + 2] as usize
+            }
+            Some(false) => target += 3,
+            _ => break,
+        }
+    }
+    target
+}
+
+/// Rewrites a program with peephole optimizations:
+///
+/// - `Add` and `Multiply` with two immediate operands are folded into an
+///   `Add` of the result and 0
+/// - jumps with immediate conditions become unconditional, or are removed if
+///   they're never taken
+/// - jumps to other unconditional jumps go straight to the final target
+///
+/// An instruction is only rewritten when every reachable write and read has a
+/// known address, none of which fall inside the instruction. Programs using
+/// relative mode are returned as they are. Removed jumps are only dropped from
+/// the image when no jump target is read from data, and every address
+/// parameter is moved to match.
+pub fn optimize(program: &Program) -> Optimized {
+    let mut words = program.image().to_vec();
+    let analysis = match analyze(&words) {
+        Some(analysis) => analysis,
+        None => {
+            return Optimized {
+                program: program.clone(),
+                addresses: (0..words.len()).collect(),
+            }
+        }
+    };
+
+    let mut removed = BTreeSet::new();
+    for (address, instruction) in analysis.instructions.iter() {
+        let address = *address;
+        if !analysis.is_fixed(address) {
+            continue;
+        }
+        let immediate = instruction.mode_1 == IntcodeMode::Immediate
+            && instruction.mode_2 == IntcodeMode::Immediate;
+        match instruction.operation {
+            IntcodeOperation::Add | IntcodeOperation::Multiply if immediate => {
+                let (a, b) = (words[address + 1], words[address + 2]);
+                let value = match instruction.operation {
+                    IntcodeOperation::Add => a.checked_add(b),
+                    _ => a.checked_mul(b),
+                };
+                if let Some(value) = value {
+                    words[address] = 1101;
+                    words[address + 1] = value;
+                    words[address + 2] = 0;
+                }
+            }
+            _ => match constant_jump(&words, address, instruction) {
+                Some(true) => {
+                    words[address] = if instruction.mode_2 == IntcodeMode::Immediate {
+                        1105
+                    } else {
+                        105
+                    };
+                    words[address + 1] = 1;
+                }
+                Some(false) if analysis.relocatable => {
+                    removed.extend(address..address + 3);
+                }
+                _ => {}
+            },
+        }
+    }
+
+    for (address, instruction) in analysis.instructions.iter() {
+        let jump = matches!(
+            instruction.operation,
+            IntcodeOperation::JumpIfTrue | IntcodeOperation::JumpIfFalse
+        );
+        if jump
+            && instruction.mode_2 == IntcodeMode::Immediate
+            && analysis.is_fixed(*address)
+            && !removed.contains(address)
+        {
+            let target = words[address + 2] as usize;
+            words[address + 2] = thread(&words, &analysis, target) as i128;
+        }
+    }
+
+    let mut addresses = Vec::with_capacity(words.len());
+    let mut next = 0;
+    for address in 0..words.len() {
+        addresses.push(next);
+        if !removed.contains(&address) {
+            next += 1;
+        }
+    }
+    let relocate = |word: i128| {
+        if word < 0 {
+            word
+        } else if (word as usize) < addresses.len() {
+            addresses[word as usize] as i128
+        } else {
+            word - removed.len() as i128
+        }
+    };
+    let intcodes = words
+        .iter()
+        .enumerate()
+        .filter(|(address, _)| !removed.contains(address))
+        .map(|(address, word)| {
+            if !removed.is_empty() && analysis.address_params.contains(&address) {
+                relocate(*word)
+            } else {
+                *word
+            }
+        })
+        .collect();
+    Optimized {
+        program: Program::new(intcodes),
+        addresses,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn outputs(program: &Program, inputs: Vec<i128>) -> Vec<i128> {
+        let mut computer = program.spawn(inputs, false);
+        computer.run();
+        computer.outputs
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        // mem[9] = 6 * 7, then output it
+        let program = Program::new(vec![1102, 6, 7, 9, 4, 9, 99, 0, 0, 0]);
+        let optimized = optimize(&program);
+        assert_eq!(
+            optimized.program.image().to_vec(),
+            vec![1101, 42, 0, 9, 4, 9, 99, 0, 0, 0]
+        );
+        assert_eq!(outputs(&optimized.program, vec![]), vec![42]);
+    }
+
+    #[test]
+    fn test_remove_and_thread_jumps() {
+        // Never taken jump at 0, a jump at 3 to an unconditional jump at 9,
+        // which goes on to output the input
+        let program = Program::new(vec![
+            1105, 0, 6, 1105, 1, 9, 104, -1, 99, 1106, 0, 12, 3, 17, 4, 17, 99, 0,
+        ]);
+        let optimized = optimize(&program);
+        assert_eq!(
+            optimized.program.image().to_vec(),
+            vec![1105, 1, 9, 104, -1, 99, 1105, 1, 9, 3, 14, 4, 14, 99, 0]
+        );
+        assert_eq!(optimized.address(0), 0);
+        assert_eq!(optimized.address(3), 0);
+        assert_eq!(optimized.address(12), 9);
+        assert_eq!(optimized.address(20), 17);
+        assert_eq!(outputs(&optimized.program, vec![5]), vec![5]);
+    }
+
+    #[test]
+    fn test_self_modifying_unchanged() {
+        // Overwrites the operands of the add at 4 before running it
+        let program = Program::new(vec![1101, 7, 0, 5, 1101, 1, 1, 12, 4, 12, 99, 0, 0]);
+        let optimized = optimize(&program);
+        assert_eq!(optimized.program, program);
+        assert_eq!(outputs(&optimized.program, vec![]), vec![8]);
+
+        // Relative mode addresses aren't known ahead of time
+        let program = Program::new(vec![109, 1, 1102, 2, 3, 7, 204, 6, 99]);
+        assert_eq!(optimize(&program).program, program);
+    }
+}
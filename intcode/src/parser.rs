@@ -0,0 +1,194 @@
+use crate::program::Program;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum ParseError {
+    /// A token between commas that isn't an integer
+    InvalidToken {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    /// Nothing between two commas, or a comma at the very start
+    EmptyToken {
+        line: usize,
+        column: usize,
+    },
+    /// Nothing but whitespace, which would otherwise run as a lone halt
+    EmptyProgram,
+    Io(io::Error),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidToken {
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "invalid token {:?} at line {}, column {}",
+                token, line, column
+            ),
+            ParseError::EmptyToken { line, column } => {
+                write!(f, "missing value at line {}, column {}", line, column)
+            }
+            ParseError::EmptyProgram => write!(f, "empty program at line 1, column 1"),
+            ParseError::Io(err) => write!(f, "unable to read program: {}", err),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> ParseError {
+        ParseError::Io(err)
+    }
+}
+
+/// Parses comma-separated intcodes. Whitespace and newlines are allowed around
+/// any value and a single trailing comma is ignored, but anything else that
+/// isn't an integer, or an empty program, is an error rather than a silent 0.
+pub fn parse_intcodes(contents: &str) -> Result<Vec<i128>, ParseError> {
+    let mut intcodes = Vec::new();
+    // Line and column of the next character, both starting at 1
+    let (mut line, mut column) = (1, 1);
+    let mut chars = contents.chars().peekable();
+
+    loop {
+        let mut token = String::new();
+        let mut start = (line, column);
+        // Whitespace after the last non-whitespace character of a token
+        let mut trailing = String::new();
+        while let Some(c) = chars.peek().copied() {
+            if c == ',' {
+                break;
+            }
+            chars.next();
+            if c.is_whitespace() {
+                if !token.is_empty() {
+                    trailing.push(c);
+                }
+            } else {
+                if token.is_empty() {
+                    start = (line, column);
+                }
+                token.push_str(&trailing);
+                trailing.clear();
+                token.push(c);
+            }
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        let at_end = chars.next().is_none();
+        if token.is_empty() {
+            if at_end && intcodes.is_empty() && contents.trim().is_empty() {
+                return Err(ParseError::EmptyProgram);
+            }
+            // Allow a trailing comma
+            if at_end && contents.trim_end().ends_with(',') {
+                return Ok(intcodes);
+            }
+            return Err(ParseError::EmptyToken {
+                line: start.0,
+                column: start.1,
+            });
+        }
+        match token.parse::<i128>() {
+            Ok(value) => intcodes.push(value),
+            Err(_) => {
+                return Err(ParseError::InvalidToken {
+                    line: start.0,
+                    column: start.1,
+                    token,
+                })
+            }
+        }
+        if at_end {
+            return Ok(intcodes);
+        }
+        column += 1;
+    }
+}
+
+impl FromStr for Program {
+    type Err = ParseError;
+
+    fn from_str(contents: &str) -> Result<Program, ParseError> {
+        Ok(Program::new(parse_intcodes(contents)?))
+    }
+}
+
+impl Program {
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Program, ParseError> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        contents.parse()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Program, ParseError> {
+        Program::from_reader(File::open(path)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_whitespace() {
+        assert_eq!(parse_intcodes("1, 2,\n-3 ,4\n").unwrap(), vec![1, 2, -3, 4]);
+        assert_eq!(parse_intcodes("99,\n").unwrap(), vec![99]);
+        match parse_intcodes(" \n") {
+            Err(ParseError::EmptyProgram) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(parse_intcodes("").is_err());
+    }
+
+    #[test]
+    fn test_parse_error_locations() {
+        match parse_intcodes("1,2,\n3,  4x,5") {
+            Err(ParseError::InvalidToken {
+                line,
+                column,
+                token,
+            }) => assert_eq!((line, column, token.as_str()), (2, 5, "4x")),
+            other => panic!("unexpected result {:?}", other),
+        }
+        match parse_intcodes("1,2 3") {
+            Err(ParseError::InvalidToken { token, .. }) => assert_eq!(token, "2 3"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        match parse_intcodes("1,,2") {
+            Err(ParseError::EmptyToken { line, column }) => assert_eq!((line, column), (1, 3)),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_program_from_reader() {
+        let program = Program::from_reader("1101,1,1,0,99\n".as_bytes()).unwrap();
+        assert_eq!(program.image().to_vec(), vec![1101, 1, 1, 0, 99]);
+        assert!(Program::load("does-not-exist.txt").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use crate::computer::IntcodeComputer;
+use crate::memory::Memory;
+use std::fmt;
+
+/// Immutable program image that any number of machines can be spawned from.
+/// Each machine shares the image until it writes to a page of memory, so
+/// every run starts from the pristine program without a full copy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Program {
+    image: Memory,
+}
+
+impl Program {
+    pub fn new(intcodes: Vec<i128>) -> Program {
+        Program {
+            image: Memory::from(intcodes),
+        }
+    }
+
+    pub fn image(&self) -> &Memory {
+        &self.image
+    }
+
+    pub fn len(&self) -> usize {
+        self.image.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.image.is_empty()
+    }
+
+    pub fn spawn(&self, inputs: Vec<i128>, pause_on_output: bool) -> IntcodeComputer {
+        IntcodeComputer::with_memory(self.image.clone(), inputs, pause_on_output)
+    }
+}
+
+impl From<Vec<i128>> for Program {
+    fn from(intcodes: Vec<i128>) -> Program {
+        Program::new(intcodes)
+    }
+}
+
+/// Formats the program as comma-separated text, the same format it's parsed
+/// from
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let words: Vec<String> = self.image.iter().map(|word| word.to_string()).collect();
+        write!(f, "{}", words.join(","))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_spawn_starts_pristine() {
+        let program = Program::new(vec![1101, 2, 3, 0, 99]);
+        let mut computer = program.spawn(vec![], false);
+        computer.run();
+        assert_eq!(computer.intcodes[0], 5);
+        assert_eq!(program.image()[0], 1101);
+        assert_eq!(
+            program.spawn(vec![], false).intcodes,
+            vec![1101, 2, 3, 0, 99]
+        );
+    }
+
+    #[test]
+    fn test_spawn_copies_on_write() {
+        // Writes only to the last page of a 1000 cell program
+        let mut intcodes = vec![0; 1000];
+        intcodes[0] = 1101;
+        intcodes[3] = 999;
+        intcodes[4] = 99;
+        let program = Program::new(intcodes);
+        let mut computer = program.spawn(vec![], false);
+        assert_eq!(computer.intcodes.shared_pages(program.image()), 4);
+        computer.run();
+        assert_eq!(computer.intcodes.shared_pages(program.image()), 3);
+        assert_eq!(program.image()[999], 0);
+    }
+}
@@ -0,0 +1,361 @@
+use crate::computer::IntcodeComputer;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Supplies values to `Input` instructions once a machine's `inputs` run out.
+/// `step` is the number of instructions the machine has executed so far.
+pub trait InputSource: Send {
+    fn next_input(&mut self, step: usize) -> Option<i128>;
+
+    /// Called with each value the machine reads from its own `inputs` instead
+    /// of asking the source
+    fn on_queued_input(&mut self, _step: usize, _value: i128) {}
+}
+
+/// Holds the input source attached to a machine, if any
+#[derive(Clone, Default)]
+pub struct InputSlot(Option<Arc<Mutex<dyn InputSource>>>);
+
+impl InputSlot {
+    pub(crate) fn next_input(&self, step: usize) -> Option<i128> {
+        self.0
+            .as_ref()
+            .and_then(|source| source.lock().unwrap().next_input(step))
+    }
+
+    pub(crate) fn on_queued_input(&self, step: usize, value: i128) {
+        if let Some(source) = &self.0 {
+            source.lock().unwrap().on_queued_input(step, value);
+        }
+    }
+}
+
+impl fmt::Debug for InputSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "InputSlot(attached)"),
+            None => write!(f, "InputSlot(none)"),
+        }
+    }
+}
+
+impl IntcodeComputer {
+    pub fn set_input_source(&mut self, source: Arc<Mutex<dyn InputSource>>) {
+        self.input_source = InputSlot(Some(source));
+    }
+}
+
+/// Prompts for each input on stdout and reads it from a line of stdin, asking
+/// again until it gets an integer or stdin is closed
+pub struct StdinInput;
+
+impl InputSource for StdinInput {
+    fn next_input(&mut self, _step: usize) -> Option<i128> {
+        loop {
+            println!("Please enter input:");
+            io::stdout().flush().ok()?;
+            let mut input = String::new();
+            if io::stdin().lock().read_line(&mut input).ok()? == 0 {
+                return None;
+            }
+            match input.trim().parse::<i128>() {
+                Ok(value) => return Some(value),
+                Err(_) => eprintln!("Invalid input {:?}", input.trim()),
+            }
+        }
+    }
+}
+
+/// Every value an input source supplied, along with the step it was read at
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    pub inputs: Vec<(usize, i128)>,
+}
+
+impl Recording {
+    /// Parses a replay file with a step and a value on each line. Blank lines
+    /// and lines starting with `#` are skipped.
+    pub fn parse(contents: &str) -> Result<Recording, String> {
+        let inputs = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim().starts_with('#'))
+            .map(|(idx, line)| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                match parts.as_slice() {
+                    [step, value] => match (step.parse(), value.parse()) {
+                        (Ok(step), Ok(value)) => Ok((step, value)),
+                        _ => Err(format!("invalid input on line {}: {:?}", idx + 1, line)),
+                    },
+                    _ => Err(format!("invalid input on line {}: {:?}", idx + 1, line)),
+                }
+            })
+            .collect::<Result<Vec<(usize, i128)>, String>>()?;
+        Ok(Recording { inputs })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Recording, String> {
+        Recording::parse(&fs::read_to_string(path).map_err(|err| err.to_string())?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# step value")?;
+        for (step, value) in self.inputs.iter() {
+            writeln!(f, "{} {}", step, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Passes inputs through from another source while recording them, along
+/// with any inputs the machine was given directly
+pub struct InputRecorder<S: InputSource> {
+    source: S,
+    pub recording: Recording,
+    log: Option<Box<dyn Write + Send>>,
+    log_error: Option<io::Error>,
+}
+
+impl<S: InputSource> InputRecorder<S> {
+    pub fn new(source: S) -> InputRecorder<S> {
+        InputRecorder {
+            source,
+            recording: Recording::default(),
+            log: None,
+            log_error: None,
+        }
+    }
+
+    /// Also writes each input to `log` in the replay file format as soon as
+    /// it's read, so the recording survives the program crashing
+    pub fn with_log<W: Write + Send + 'static>(mut self, mut log: W) -> io::Result<Self> {
+        write!(log, "{}", self.recording)?;
+        log.flush()?;
+        self.log = Some(Box::new(log));
+        Ok(self)
+    }
+
+    /// The first error writing to the log, after which it stops being written
+    pub fn log_error(&self) -> Option<&io::Error> {
+        self.log_error.as_ref()
+    }
+
+    fn record(&mut self, step: usize, value: i128) {
+        self.recording.inputs.push((step, value));
+        if let Some(log) = &mut self.log {
+            if let Err(err) = writeln!(log, "{} {}", step, value).and_then(|_| log.flush()) {
+                self.log = None;
+                self.log_error = Some(err);
+            }
+        }
+    }
+}
+
+impl<S: InputSource> InputSource for InputRecorder<S> {
+    fn next_input(&mut self, step: usize) -> Option<i128> {
+        let value = self.source.next_input(step)?;
+        self.record(step, value);
+        Some(value)
+    }
+
+    fn on_queued_input(&mut self, step: usize, value: i128) {
+        self.source.on_queued_input(step, value);
+        self.record(step, value);
+    }
+}
+
+/// Feeds recorded inputs back in order. If the program asks for an input at a
+/// different step than it was recorded at, the run has diverged from the
+/// recording, and the first place that happened is kept. Inputs the machine
+/// is given directly use up their place in the recording too.
+pub struct Replayer {
+    recording: Recording,
+    position: usize,
+    /// Recorded and actual step of the first mismatched input
+    pub divergence: Option<(usize, usize)>,
+    /// Whether the program asked for more inputs than were recorded
+    pub exhausted: bool,
+}
+
+impl Replayer {
+    pub fn new(recording: Recording) -> Replayer {
+        Replayer {
+            recording,
+            position: 0,
+            divergence: None,
+            exhausted: false,
+        }
+    }
+
+    /// Whether every recorded input has been consumed
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.recording.inputs.len()
+    }
+
+    /// Number of recorded inputs not consumed yet
+    pub fn remaining(&self) -> usize {
+        self.recording.inputs.len().saturating_sub(self.position)
+    }
+
+    fn advance(&mut self, step: usize) -> Option<i128> {
+        let (recorded_step, value) = match self.recording.inputs.get(self.position) {
+            Some(input) => *input,
+            None => {
+                self.exhausted = true;
+                return None;
+            }
+        };
+        if recorded_step != step && self.divergence.is_none() {
+            self.divergence = Some((recorded_step, step));
+        }
+        self.position += 1;
+        Some(value)
+    }
+}
+
+impl InputSource for Replayer {
+    fn next_input(&mut self, step: usize) -> Option<i128> {
+        self.advance(step)
+    }
+
+    fn on_queued_input(&mut self, step: usize, _value: i128) {
+        self.advance(step);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::computer::IntcodeOperation;
+
+    struct Scripted(Vec<i128>);
+
+    impl InputSource for Scripted {
+        fn next_input(&mut self, _step: usize) -> Option<i128> {
+            if self.0.is_empty() {
+                None
+            } else {
+                Some(self.0.remove(0))
+            }
+        }
+    }
+
+    /// Log that can be read back after the recorder takes it
+    struct SharedLog(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Reads two inputs and outputs their product
+    const PROGRAM: [i128; 14] = [3, 13, 3, 12, 2, 12, 13, 11, 4, 11, 99, 0, 0, 0];
+
+    #[test]
+    fn test_record_and_replay() {
+        let recorder = Arc::new(Mutex::new(InputRecorder::new(Scripted(vec![6, 7]))));
+        let mut computer = IntcodeComputer::new(PROGRAM.to_vec(), vec![], false);
+        computer.set_input_source(recorder.clone());
+        computer.run();
+        assert_eq!(computer.outputs, vec![42]);
+
+        let recording = recorder.lock().unwrap().recording.clone();
+        assert_eq!(recording.inputs, vec![(0, 6), (1, 7)]);
+        let recording = Recording::parse(&recording.to_string()).unwrap();
+
+        let replayer = Arc::new(Mutex::new(Replayer::new(recording)));
+        let mut replay = IntcodeComputer::new(PROGRAM.to_vec(), vec![], false);
+        replay.set_input_source(replayer.clone());
+        replay.run();
+        assert_eq!(replay.outputs, vec![42]);
+        assert!(replayer.lock().unwrap().is_finished());
+        assert_eq!(replayer.lock().unwrap().divergence, None);
+    }
+
+    #[test]
+    fn test_replay_divergence() {
+        let recording = Recording {
+            inputs: vec![(0, 6), (5, 7)],
+        };
+        let replayer = Arc::new(Mutex::new(Replayer::new(recording)));
+        let mut computer = IntcodeComputer::new(PROGRAM.to_vec(), vec![], false);
+        computer.set_input_source(replayer.clone());
+        computer.run();
+        assert_eq!(replayer.lock().unwrap().divergence, Some((5, 1)));
+    }
+
+    #[test]
+    fn test_queued_inputs_and_log() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let recorder = InputRecorder::new(Scripted(vec![7]))
+            .with_log(SharedLog(log.clone()))
+            .unwrap();
+        let recorder = Arc::new(Mutex::new(recorder));
+        let mut computer = IntcodeComputer::new(PROGRAM.to_vec(), vec![6], false);
+        computer.set_input_source(recorder.clone());
+        computer.run();
+        assert_eq!(computer.outputs, vec![42]);
+
+        // Both inputs are on disk, including the one queued up front
+        let written = String::from_utf8(log.lock().unwrap().clone()).unwrap();
+        let recording = Recording::parse(&written).unwrap();
+        assert_eq!(recording, recorder.lock().unwrap().recording);
+        assert_eq!(recording.inputs, vec![(0, 6), (1, 7)]);
+
+        // Replaying with the first input queued again skips past it
+        let replayer = Arc::new(Mutex::new(Replayer::new(recording)));
+        let mut replay = IntcodeComputer::new(PROGRAM.to_vec(), vec![6], false);
+        replay.set_input_source(replayer.clone());
+        replay.run();
+        assert_eq!(replay.outputs, vec![42]);
+        assert!(replayer.lock().unwrap().is_finished());
+        assert!(!replayer.lock().unwrap().exhausted);
+    }
+
+    #[test]
+    fn test_replay_exhausted() {
+        let recording = Recording {
+            inputs: vec![(0, 6)],
+        };
+        let replayer = Arc::new(Mutex::new(Replayer::new(recording)));
+        let mut computer = IntcodeComputer::new(PROGRAM.to_vec(), vec![], false);
+        computer.set_pause_on_input(true);
+        computer.set_input_source(replayer.clone());
+        assert_eq!(computer.run(), IntcodeOperation::Input);
+        assert!(replayer.lock().unwrap().exhausted);
+    }
+
+    #[test]
+    fn test_pause_on_input() {
+        let mut computer = IntcodeComputer::new(PROGRAM.to_vec(), vec![], false);
+        computer.set_pause_on_input(true);
+        computer.set_input_source(Arc::new(Mutex::new(Scripted(vec![6]))));
+        assert_eq!(computer.run(), IntcodeOperation::Input);
+        assert_eq!((computer.index, computer.steps), (2, 1));
+        assert_eq!(computer.step(), Some(IntcodeOperation::Input));
+        assert_eq!(computer.index, 2);
+
+        computer.inputs.push(7);
+        assert_eq!(computer.run(), IntcodeOperation::Halt);
+        assert_eq!(computer.outputs, vec![42]);
+    }
+
+    #[test]
+    fn test_parse_invalid_recording() {
+        assert!(Recording::parse("# step value\n0 1\n1\n").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use crate::computer::{IntcodeComputer, IntcodeOperation};
+use crate::program::Program;
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Sweeps every combination of values for a set of patched addresses, running
+/// each patched program to completion and checking the result with a predicate.
+/// Assignments that fault or run out of steps never match.
+pub struct ParameterSearch {
+    program: Program,
+    inputs: Vec<i128>,
+    patches: Vec<(usize, Range<i128>)>,
+    threads: usize,
+    step_limit: usize,
+}
+
+impl ParameterSearch {
+    pub fn new(
+        program: &Program,
+        inputs: Vec<i128>,
+        patches: Vec<(usize, Range<i128>)>,
+    ) -> ParameterSearch {
+        ParameterSearch {
+            program: program.clone(),
+            inputs,
+            patches,
+            threads: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            step_limit: 1_000_000,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> ParameterSearch {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Most instructions to run for each assignment before giving up on it
+    pub fn with_step_limit(mut self, step_limit: usize) -> ParameterSearch {
+        self.step_limit = step_limit;
+        self
+    }
+
+    /// Total number of assignments in the search space, saturating at
+    /// `usize::MAX`
+    pub fn len(&self) -> usize {
+        self.patches
+            .iter()
+            .try_fold(1usize, |total, (_, range)| {
+                total.checked_mul(range_len(range))
+            })
+            .unwrap_or(usize::MAX)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the assignment at position `n` of the search space, with the
+    /// last patch varying fastest
+    fn assignment(&self, mut n: usize) -> Vec<i128> {
+        let mut values = vec![0; self.patches.len()];
+        for (i, (_, range)) in self.patches.iter().enumerate().rev() {
+            let size = range_len(range);
+            values[i] = range.start + (n % size) as i128;
+            n /= size;
+        }
+        values
+    }
+
+    /// Runs an assignment to completion, or returns `None` if it faults or
+    /// runs out of steps
+    fn run_assignment(&self, values: &[i128]) -> Option<IntcodeComputer> {
+        let mut computer = self.program.spawn(self.inputs.clone(), false);
+        for ((address, _), value) in self.patches.iter().zip(values) {
+            computer.intcodes.extend(address + 1);
+            computer.intcodes[*address] = *value;
+        }
+        for _ in 0..self.step_limit {
+            match computer.try_step() {
+                Ok(Some(IntcodeOperation::Halt)) => return Some(computer),
+                Err(_) => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn matches<F: Fn(&IntcodeComputer) -> bool>(&self, n: usize, predicate: F) -> bool {
+        self.run_assignment(&self.assignment(n))
+            .is_some_and(|computer| predicate(&computer))
+    }
+
+    /// Returns the first matching assignment in search order, even though the
+    /// space is explored out of order across threads
+    pub fn find_first<F>(&self, predicate: F) -> Option<Vec<i128>>
+    where
+        F: Fn(&IntcodeComputer) -> bool + Sync,
+    {
+        let total = self.len();
+        let best = AtomicUsize::new(total);
+        thread::scope(|s| {
+            for offset in 0..self.threads {
+                let best = &best;
+                let predicate = &predicate;
+                s.spawn(move || {
+                    let mut n = offset;
+                    while n < total && n < best.load(Ordering::Relaxed) {
+                        if self.matches(n, predicate) {
+                            best.fetch_min(n, Ordering::Relaxed);
+                            return;
+                        }
+                        n += self.threads;
+                    }
+                });
+            }
+        });
+        match best.into_inner() {
+            n if n < total => Some(self.assignment(n)),
+            _ => None,
+        }
+    }
+
+    /// Returns every matching assignment in search order
+    pub fn find_all<F>(&self, predicate: F) -> Vec<Vec<i128>>
+    where
+        F: Fn(&IntcodeComputer) -> bool + Sync,
+    {
+        let total = self.len();
+        let matches = Mutex::new(Vec::new());
+        thread::scope(|s| {
+            for offset in 0..self.threads {
+                let matches = &matches;
+                let predicate = &predicate;
+                s.spawn(move || {
+                    let found: Vec<usize> = (offset..total)
+                        .step_by(self.threads)
+                        .filter(|n| self.matches(*n, predicate))
+                        .collect();
+                    matches.lock().unwrap().extend(found);
+                });
+            }
+        });
+        let mut matches = matches.into_inner().unwrap();
+        matches.sort_unstable();
+        matches.into_iter().map(|n| self.assignment(n)).collect()
+    }
+}
+
+/// Number of values in a range, saturating at `usize::MAX`
+fn range_len(range: &Range<i128>) -> usize {
+    match range.end.checked_sub(range.start) {
+        Some(len) if len <= 0 => 0,
+        Some(len) => usize::try_from(len).unwrap_or(usize::MAX),
+        None => usize::MAX,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_first() {
+        // Adds the immediate values at positions 1 and 2 into position 0
+        let program = Program::new(vec![1101, 0, 0, 0, 99]);
+        let search = ParameterSearch::new(&program, vec![], vec![(1, 0..5), (2, 0..5)]);
+        let result = search.find_first(|c| c.intcodes[0] == 5);
+        assert_eq!(result, Some(vec![1, 4]));
+    }
+
+    #[test]
+    fn test_find_all() {
+        let program = Program::new(vec![1101, 0, 0, 0, 99]);
+        let search =
+            ParameterSearch::new(&program, vec![], vec![(1, 0..5), (2, 0..5)]).with_threads(3);
+        let result = search.find_all(|c| c.intcodes[0] == 5);
+        assert_eq!(result, vec![vec![1, 4], vec![2, 3], vec![3, 2], vec![4, 1]]);
+    }
+
+    #[test]
+    fn test_find_on_outputs() {
+        // Outputs the sum of the input and position 9
+        let program = Program::new(vec![3, 11, 1, 11, 9, 11, 4, 11, 99, 0, 0, 0]);
+        let search = ParameterSearch::new(&program, vec![7], vec![(9, -10..10)]);
+        assert_eq!(search.find_first(|c| c.outputs == vec![3]), Some(vec![-4]));
+        assert_eq!(search.find_first(|c| c.outputs == vec![30]), None);
+    }
+
+    #[test]
+    fn test_len_saturates() {
+        let program = Program::new(vec![99]);
+        let search = ParameterSearch::new(&program, vec![], vec![(0, i128::MIN..i128::MAX)]);
+        assert_eq!(search.len(), usize::MAX);
+        let search = ParameterSearch::new(&program, vec![], vec![(0, 0..1 << 40), (1, 0..1 << 40)]);
+        assert_eq!(search.len(), usize::MAX);
+        let search = ParameterSearch::new(
+            &program,
+            vec![],
+            vec![(0, 0..1 << 40), (1, Range { start: 5, end: 0 })],
+        );
+        assert!(search.is_empty());
+    }
+
+    #[test]
+    fn test_faults_and_loops_never_match() {
+        // Jumps to position 2, which either halts, loops forever, or
+        // overflows an add
+        let program = Program::new(vec![1105, 1, 0, 99, 1105, 1, 4, 1101, i128::MAX, 1, 0, 99]);
+        let search = ParameterSearch::new(&program, vec![], vec![(2, 3..5)]).with_step_limit(100);
+        assert_eq!(search.find_all(|_| true), vec![vec![3]]);
+        let search = ParameterSearch::new(&program, vec![], vec![(2, 7..8)]);
+        assert_eq!(search.find_first(|_| true), None);
+    }
+}
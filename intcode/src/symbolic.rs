@@ -0,0 +1,519 @@
+use crate::computer::{IntcodeInstruction, IntcodeMode, IntcodeOperation};
+use crate::program::Program;
+use crate::search::ParameterSearch;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Range;
+
+/// Upper bound on instructions executed symbolically before giving up
+const MAX_STEPS: usize = 1_000_000;
+/// Largest memory, in words, the symbolic machine grows to before giving up
+const MAX_MEMORY: usize = 1 << 20;
+
+/// Polynomial over the patched cells with integer coefficients, keyed by the
+/// exponent of each unknown in a term
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial {
+    terms: BTreeMap<Vec<u32>, i128>,
+}
+
+impl Polynomial {
+    pub fn constant(value: i128) -> Polynomial {
+        let mut terms = BTreeMap::new();
+        if value != 0 {
+            terms.insert(vec![], value);
+        }
+        Polynomial { terms }
+    }
+
+    pub fn variable(var: usize) -> Polynomial {
+        let mut exponents = vec![0; var + 1];
+        exponents[var] = 1;
+        let mut terms = BTreeMap::new();
+        terms.insert(exponents, 1);
+        Polynomial { terms }
+    }
+
+    /// Returns the value if the polynomial doesn't depend on any unknowns
+    pub fn as_constant(&self) -> Option<i128> {
+        match self.terms.len() {
+            0 => Some(0),
+            1 => self.terms.get(&vec![]).copied(),
+            _ => None,
+        }
+    }
+
+    pub fn degree_in(&self, var: usize) -> u32 {
+        self.terms
+            .keys()
+            .map(|exps| *exps.get(var).unwrap_or(&0))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Value for an assignment of the unknowns, or `None` if it overflows
+    pub fn evaluate(&self, values: &[i128]) -> Option<i128> {
+        self.terms.iter().try_fold(0_i128, |sum, (exps, coeff)| {
+            let term = exps
+                .iter()
+                .enumerate()
+                .try_fold(*coeff, |acc, (var, exp)| {
+                    acc.checked_mul(values[var].checked_pow(*exp)?)
+                })?;
+            sum.checked_add(term)
+        })
+    }
+
+    /// Sum of two polynomials, or `None` if a coefficient overflows
+    pub fn checked_add(&self, other: &Polynomial) -> Option<Polynomial> {
+        let mut result = self.clone();
+        for (exps, value) in other.terms.iter() {
+            result.insert(exps.clone(), *value)?;
+        }
+        Some(result)
+    }
+
+    /// Product of two polynomials, or `None` if a coefficient overflows
+    pub fn checked_mul(&self, other: &Polynomial) -> Option<Polynomial> {
+        let mut result = Polynomial::constant(0);
+        for (exps_a, value_a) in self.terms.iter() {
+            for (exps_b, value_b) in other.terms.iter() {
+                result.insert(
+                    monomial_product(exps_a, exps_b),
+                    value_a.checked_mul(*value_b)?,
+                )?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Splits a polynomial of degree at most 1 in `var` into `(a, b)` such that
+    /// the polynomial equals `a * var + b`
+    fn split_linear(&self, var: usize) -> (Polynomial, Polynomial) {
+        let mut coeff = Polynomial::constant(0);
+        let mut rest = Polynomial::constant(0);
+        for (exps, value) in self.terms.iter() {
+            if *exps.get(var).unwrap_or(&0) == 1 {
+                let mut exps = exps.clone();
+                exps[var] = 0;
+                coeff.terms.insert(normalize(exps), *value);
+            } else {
+                rest.terms.insert(exps.clone(), *value);
+            }
+        }
+        (coeff, rest)
+    }
+
+    /// Adds a term, returning `None` if its coefficient overflows
+    fn insert(&mut self, exps: Vec<u32>, value: i128) -> Option<()> {
+        let entry = self.terms.entry(exps).or_insert(0);
+        *entry = entry.checked_add(value)?;
+        if *entry == 0 {
+            self.terms.retain(|_, v| *v != 0);
+        }
+        Some(())
+    }
+}
+
+/// Trims trailing zero exponents so equal monomials share a key
+fn normalize(mut exps: Vec<u32>) -> Vec<u32> {
+    while exps.last() == Some(&0) {
+        exps.pop();
+    }
+    exps
+}
+
+/// Multiplying monomials adds their exponents
+fn monomial_product(exps_a: &[u32], exps_b: &[u32]) -> Vec<u32> {
+    let len = exps_a.len().max(exps_b.len());
+    let exps = (0..len)
+        .map(|i| exps_a.get(i).unwrap_or(&0) + exps_b.get(i).unwrap_or(&0))
+        .collect();
+    normalize(exps)
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        // Highest degree terms first
+        for (idx, (exps, coeff)) in self.terms.iter().rev().enumerate() {
+            let factors: Vec<String> = exps
+                .iter()
+                .enumerate()
+                .filter(|(_, exp)| **exp > 0)
+                .map(|(var, exp)| match exp {
+                    1 => format!("x{}", var),
+                    _ => format!("x{}^{}", var, exp),
+                })
+                .collect();
+            let sign = if *coeff < 0 { "-" } else { "+" };
+            if idx == 0 {
+                if *coeff < 0 {
+                    write!(f, "-")?;
+                }
+            } else {
+                write!(f, " {} ", sign)?;
+            }
+            match (coeff.abs(), factors.is_empty()) {
+                (c, true) => write!(f, "{}", c)?,
+                (1, false) => write!(f, "{}", factors.join("*"))?,
+                (c, false) => write!(f, "{}*{}", c, factors.join("*"))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Solves programs with patched input cells, like day 2, by running them once
+/// with the patched cells as unknowns and solving the resulting expression.
+/// Falls back to a brute force `ParameterSearch` whenever control flow or
+/// addressing depends on the unknowns.
+pub struct SymbolicSolver {
+    program: Program,
+    inputs: Vec<i128>,
+    patches: Vec<(usize, Range<i128>)>,
+}
+
+impl SymbolicSolver {
+    pub fn new(
+        program: &Program,
+        inputs: Vec<i128>,
+        patches: Vec<(usize, Range<i128>)>,
+    ) -> SymbolicSolver {
+        SymbolicSolver {
+            program: program.clone(),
+            inputs,
+            patches,
+        }
+    }
+
+    /// Runs the program symbolically and returns the expression left at
+    /// `address` after halting, or `None` if it can't be run symbolically
+    pub fn expression(&self, address: usize) -> Option<Polynomial> {
+        let mut memory: Vec<Option<Polynomial>> = self
+            .program
+            .image()
+            .iter()
+            .map(|v| Some(Polynomial::constant(*v)))
+            .collect();
+        for (var, (patch_address, _)) in self.patches.iter().enumerate() {
+            if *patch_address >= memory.len() {
+                memory.resize(patch_address + 1, Some(Polynomial::constant(0)));
+            }
+            memory[*patch_address] = Some(Polynomial::variable(var));
+        }
+        let mut machine = SymbolicMachine {
+            memory,
+            inputs: self.inputs.clone(),
+            outputs: Vec::new(),
+            index: 0,
+            relative_base: 0,
+        };
+        for _ in 0..MAX_STEPS {
+            if !machine.step()? {
+                return machine.read(address as i128);
+            }
+        }
+        None
+    }
+
+    /// Returns every assignment of the patched cells that leaves `target` at
+    /// `address`, in the same order as `ParameterSearch::find_all`
+    pub fn solve(&self, address: usize, target: i128) -> Vec<Vec<i128>> {
+        let solutions = self
+            .expression(address)
+            .and_then(|poly| self.solve_polynomial(&poly, target));
+        match solutions {
+            Some(solutions) => solutions,
+            None => self
+                .search()
+                .find_all(|c| c.intcodes.get(address) == Some(&target)),
+        }
+    }
+
+    pub fn solve_first(&self, address: usize, target: i128) -> Option<Vec<i128>> {
+        let solutions = self
+            .expression(address)
+            .and_then(|poly| self.solve_polynomial(&poly, target));
+        match solutions {
+            Some(solutions) => solutions.into_iter().next(),
+            None => self
+                .search()
+                .find_first(|c| c.intcodes.get(address) == Some(&target)),
+        }
+    }
+
+    fn search(&self) -> ParameterSearch {
+        ParameterSearch::new(&self.program, self.inputs.clone(), self.patches.clone())
+    }
+
+    /// Enumerates every unknown except one that appears linearly, which is
+    /// solved for directly. If no unknown is linear, every assignment is
+    /// evaluated, which is still far cheaper than running the program. Returns
+    /// `None` if evaluating overflows, since the program might not.
+    fn solve_polynomial(&self, poly: &Polynomial, target: i128) -> Option<Vec<Vec<i128>>> {
+        let linear_var = (0..self.patches.len())
+            .rev()
+            .find(|var| poly.degree_in(*var) <= 1);
+        let enumerated: Vec<usize> = (0..self.patches.len())
+            .filter(|var| Some(*var) != linear_var)
+            .collect();
+        let (coeff, rest) = match linear_var {
+            Some(var) => poly.split_linear(var),
+            None => (Polynomial::constant(0), poly.clone()),
+        };
+
+        let mut solutions = Vec::new();
+        let mut values: Vec<i128> = self.patches.iter().map(|(_, r)| r.start).collect();
+        if self.patches.iter().any(|(_, r)| r.start >= r.end) {
+            return Some(solutions);
+        }
+        loop {
+            let a = coeff.evaluate(&values)?;
+            let b = rest.evaluate(&values)?;
+            match linear_var {
+                Some(var) => {
+                    let range = &self.patches[var].1;
+                    if a == 0 {
+                        if b == target {
+                            for v in range.clone() {
+                                values[var] = v;
+                                solutions.push(values.clone());
+                            }
+                        }
+                    } else {
+                        // No quotient that fits in an `i128` means no solution
+                        let diff = target.checked_sub(b)?;
+                        let value = diff
+                            .checked_div(a)
+                            .filter(|_| diff.checked_rem(a) == Some(0));
+                        if let Some(value) = value.filter(|value| range.contains(value)) {
+                            values[var] = value;
+                            solutions.push(values.clone());
+                        }
+                    }
+                }
+                None => {
+                    if b == target {
+                        solutions.push(values.clone());
+                    }
+                }
+            }
+
+            // Advance the enumerated unknowns like an odometer
+            let mut done = true;
+            for var in enumerated.iter().rev() {
+                values[*var] += 1;
+                if values[*var] < self.patches[*var].1.end {
+                    done = false;
+                    break;
+                }
+                values[*var] = self.patches[*var].1.start;
+            }
+            if done {
+                break;
+            }
+        }
+        solutions.sort();
+        Some(solutions)
+    }
+}
+
+/// Mirrors `IntcodeComputer` over polynomial cells. A cell is `None` when its
+/// value depends on the unknowns in a way that can't be tracked, like a read
+/// from an unknown address. That's fine until the cell is used for an
+/// instruction, address, jump or comparison, at which point `step` gives up
+/// by returning `None`.
+struct SymbolicMachine {
+    memory: Vec<Option<Polynomial>>,
+    inputs: Vec<i128>,
+    outputs: Vec<Option<Polynomial>>,
+    index: usize,
+    relative_base: i128,
+}
+
+impl SymbolicMachine {
+    /// Gives up on negative addresses and very large ones, like `write`,
+    /// rather than guessing what the machine would do with them
+    fn read(&self, address: i128) -> Option<Polynomial> {
+        let address = usize::try_from(address).ok().filter(|a| *a < MAX_MEMORY)?;
+        self.memory
+            .get(address)
+            .cloned()
+            .unwrap_or_else(|| Some(Polynomial::constant(0)))
+    }
+
+    /// Gives up on negative addresses, which fault, and very large ones
+    fn write(&mut self, address: i128, value: Option<Polynomial>) -> Option<()> {
+        let address = usize::try_from(address).ok().filter(|a| *a < MAX_MEMORY)?;
+        if address >= self.memory.len() {
+            self.memory
+                .resize(address + 1, Some(Polynomial::constant(0)));
+        }
+        self.memory[address] = value;
+        Some(())
+    }
+
+    fn arg_value(&self, offset: usize, mode: IntcodeMode) -> Option<Polynomial> {
+        let arg = self.read((self.index + offset) as i128)?;
+        match mode {
+            IntcodeMode::Position => self.read(arg.as_constant()?),
+            IntcodeMode::Immediate => Some(arg),
+            IntcodeMode::Relative => self.read(self.relative_base.checked_add(arg.as_constant()?)?),
+        }
+    }
+
+    fn arg_dest(&self, offset: usize, mode: IntcodeMode) -> Option<i128> {
+        let arg = self.read((self.index + offset) as i128)?.as_constant()?;
+        match mode {
+            IntcodeMode::Relative => self.relative_base.checked_add(arg),
+            _ => Some(arg),
+        }
+    }
+
+    /// Executes a single instruction, returning `Some(false)` on halt
+    fn step(&mut self) -> Option<bool> {
+        let instruction =
+            IntcodeInstruction::try_from_num(self.read(self.index as i128)?.as_constant()?)?;
+        match instruction.operation {
+            IntcodeOperation::Add | IntcodeOperation::Multiply => {
+                let a = self.arg_value(1, instruction.mode_1);
+                let b = self.arg_value(2, instruction.mode_2);
+                let dest = self.arg_dest(3, instruction.mode_3)?;
+                let value = match (a, b, instruction.operation) {
+                    (Some(a), Some(b), IntcodeOperation::Add) => Some(a.checked_add(&b)?),
+                    (Some(a), Some(b), _) => Some(a.checked_mul(&b)?),
+                    _ => None,
+                };
+                self.write(dest, value)?;
+                self.index += 4;
+            }
+            IntcodeOperation::LessThan | IntcodeOperation::Equal => {
+                let a = self.arg_value(1, instruction.mode_1)?.as_constant()?;
+                let b = self.arg_value(2, instruction.mode_2)?.as_constant()?;
+                let dest = self.arg_dest(3, instruction.mode_3)?;
+                let value = match instruction.operation {
+                    IntcodeOperation::LessThan => a < b,
+                    _ => a == b,
+                };
+                self.write(dest, Some(Polynomial::constant(value as i128)))?;
+                self.index += 4;
+            }
+            IntcodeOperation::Input => {
+                let dest = self.arg_dest(1, instruction.mode_1)?;
+                let value = if !self.inputs.is_empty() {
+                    Some(Polynomial::constant(self.inputs.remove(0)))
+                } else {
+                    self.outputs
+                        .pop()
+                        .unwrap_or_else(|| Some(Polynomial::constant(0)))
+                };
+                self.write(dest, value)?;
+                self.index += 2;
+            }
+            IntcodeOperation::Output => {
+                let value = self.arg_value(1, instruction.mode_1);
+                self.outputs.push(value);
+                self.index += 2;
+            }
+            IntcodeOperation::JumpIfTrue | IntcodeOperation::JumpIfFalse => {
+                let cond = self.arg_value(1, instruction.mode_1)?.as_constant()?;
+                let jump = (cond != 0) == (instruction.operation == IntcodeOperation::JumpIfTrue);
+                self.index = if jump {
+                    usize::try_from(self.arg_value(2, instruction.mode_2)?.as_constant()?).ok()?
+                } else {
+                    self.index + 3
+                };
+            }
+            IntcodeOperation::RelativeBaseOffset => {
+                let offset = self.arg_value(1, instruction.mode_1)?.as_constant()?;
+                self.relative_base = self.relative_base.checked_add(offset)?;
+                self.index += 2;
+            }
+            IntcodeOperation::Halt => return Some(false),
+        }
+        Some(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_polynomial_expression() {
+        // The first add reads unknown addresses, but its result is overwritten
+        // by x0 + x1 before mem[0] = (x0 + x1) * x0
+        let program = Program::new(vec![1, 0, 0, 3, 1, 1, 2, 3, 2, 3, 1, 0, 99]);
+        let solver = SymbolicSolver::new(&program, vec![], vec![(1, 0..10), (2, 0..10)]);
+        let poly = solver.expression(0).unwrap();
+        assert_eq!(poly.to_string(), "x0^2 + x0*x1");
+        assert_eq!(poly.evaluate(&[3, 4]), Some(21));
+    }
+
+    #[test]
+    fn test_solve_matches_search() {
+        let program = Program::new(vec![1, 0, 0, 3, 1, 1, 2, 3, 2, 3, 1, 0, 99]);
+        let patches = vec![(1, 0..10), (2, 0..10)];
+        let solver = SymbolicSolver::new(&program, vec![], patches.clone());
+        let search = ParameterSearch::new(&program, vec![], patches);
+        for target in [0, 2, 12, 21, 40, 41].iter().copied() {
+            assert_eq!(
+                solver.solve(0, target),
+                search.find_all(|c| c.intcodes[0] == target)
+            );
+        }
+    }
+
+    #[test]
+    fn test_gives_up_instead_of_panicking() {
+        // Invalid mode digit, negative destination, and a product that
+        // overflows
+        let programs = vec![
+            vec![1401, 0, 0, 0, 99],
+            vec![1101, 0, 0, -1, 99],
+            vec![1102, 0, 0, 0, 2, 0, 0, 0, 99],
+        ];
+        for (intcodes, target) in programs.into_iter().zip([2, 4, 0].iter()) {
+            let program = Program::new(intcodes);
+            let patches = vec![(1, i128::MAX - 1..i128::MAX), (2, 2..4)];
+            let solver = SymbolicSolver::new(&program, vec![], patches.clone());
+            let search = ParameterSearch::new(&program, vec![], patches);
+            assert_eq!(
+                solver.solve(0, *target),
+                search.find_all(|c| c.intcodes[0] == *target)
+            );
+        }
+    }
+
+    #[test]
+    fn test_division_overflow_and_bad_reads() {
+        let program = Program::new(vec![1102, 0, -1, 0, 99]);
+        let solver = SymbolicSolver::new(&program, vec![], vec![(1, -5..5)]);
+        assert_eq!(solver.solve(0, i128::MIN), Vec::<Vec<i128>>::new());
+
+        // Reads from a negative address and from past the memory limit
+        for address in [-1, 1 << 40].iter() {
+            let program = Program::new(vec![1, 0, *address, 0, 99]);
+            let patches = vec![(1, 0..3)];
+            let solver = SymbolicSolver::new(&program, vec![], patches.clone());
+            let search = ParameterSearch::new(&program, vec![], patches);
+            assert_eq!(solver.solve(0, 1), search.find_all(|c| c.intcodes[0] == 1));
+        }
+    }
+
+    #[test]
+    fn test_solve_falls_back_on_branch() {
+        // Jumps over the halt when position 1 is nonzero
+        let program = Program::new(vec![1105, 0, 7, 99, 0, 0, 0, 1101, 5, 5, 0, 99]);
+        let solver = SymbolicSolver::new(&program, vec![], vec![(1, -2..3)]);
+        assert_eq!(solver.expression(0), None);
+        assert_eq!(
+            solver.solve(0, 10),
+            vec![vec![-2], vec![-1], vec![1], vec![2]]
+        );
+    }
+}
@@ -0,0 +1,447 @@
+use crate::computer::{instruction_len, IntcodeInstruction, IntcodeMode, IntcodeOperation};
+use crate::program::Program;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Finds every instruction reachable from address 0 by following fallthrough
+/// and jump targets that can be read from the image. Targets computed at
+/// runtime can't be followed, so the generated code hands those off to the
+/// interpreter.
+pub(crate) fn reachable_instructions(program: &Program) -> BTreeMap<usize, IntcodeInstruction> {
+    let image = program.image();
+    let mut found = BTreeMap::new();
+    let mut pending = vec![0];
+    while let Some(address) = pending.pop() {
+        if found.contains_key(&address) || address >= image.len() {
+            continue;
+        }
+        let instruction = match IntcodeInstruction::try_from_num(image[address]) {
+            Some(instruction) => instruction,
+            None => continue,
+        };
+        found.insert(address, instruction);
+        let param = |n: usize| *image.get(address + n).unwrap_or(&0);
+        match instruction.operation {
+            IntcodeOperation::Halt => {}
+            IntcodeOperation::JumpIfTrue | IntcodeOperation::JumpIfFalse => {
+                pending.push(address + 3);
+                let target = match instruction.mode_2 {
+                    IntcodeMode::Immediate => Some(param(2)),
+                    IntcodeMode::Position if param(2) >= 0 => image.get(param(2) as usize).copied(),
+                    _ => None,
+                };
+                if let Some(target) = target.filter(|t| *t >= 0) {
+                    pending.push(target as usize);
+                }
+            }
+            op => pending.push(address + instruction_len(op)),
+        }
+    }
+    found
+}
+
+/// Operand words the program writes to with a destination known from the
+/// image. These are read from memory, and every other operand is loaded into
+/// a local when the run starts.
+fn written_operands(
+    program: &Program,
+    instructions: &BTreeMap<usize, IntcodeInstruction>,
+) -> BTreeSet<usize> {
+    let image = program.image();
+    instructions
+        .iter()
+        .filter_map(|(address, instruction)| {
+            let (n, mode) = match instruction.operation {
+                IntcodeOperation::Add
+                | IntcodeOperation::Multiply
+                | IntcodeOperation::LessThan
+                | IntcodeOperation::Equal => (3, instruction.mode_3),
+                IntcodeOperation::Input => (1, instruction.mode_1),
+                _ => return None,
+            };
+            let dest = *image.get(address + n)?;
+            match mode {
+                IntcodeMode::Relative => None,
+                _ if dest >= 0 => Some(dest as usize),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Expression for the operand word at `address`
+fn operand(address: usize, written: &BTreeSet<usize>) -> String {
+    if written.contains(&address) {
+        format!("param(m, {})", address)
+    } else {
+        format!("p{}", address)
+    }
+}
+
+/// Hands the instruction at `address` to the interpreter, which reports the
+/// fault or handles the address the generated code can't
+fn fallback_expr(address: usize) -> String {
+    format!("fallback(m, inputs, outputs, {}, rb)", address)
+}
+
+/// Expression for reading a parameter the same way `IntcodeComputer` does
+fn read_expr(address: usize, n: usize, mode: IntcodeMode, written: &BTreeSet<usize>) -> String {
+    let arg = operand(address + n, written);
+    match mode {
+        IntcodeMode::Position => format!(
+            "or_fallback!(position(m, {}), {})",
+            arg,
+            fallback_expr(address)
+        ),
+        IntcodeMode::Immediate => arg,
+        IntcodeMode::Relative => format!(
+            "or_fallback!(relative(m, rb, {}, false), {})",
+            arg,
+            fallback_expr(address)
+        ),
+    }
+}
+
+fn dest_expr(address: usize, n: usize, mode: IntcodeMode, written: &BTreeSet<usize>) -> String {
+    let arg = operand(address + n, written);
+    match mode {
+        IntcodeMode::Relative => format!(
+            "or_fallback!(relative(m, rb, {}, true), {})",
+            arg,
+            fallback_expr(address)
+        ),
+        _ => arg,
+    }
+}
+
+/// Generates a standalone Rust module that runs `program` the same way
+/// `IntcodeComputer::run` does without pausing on output, with one `match` arm
+/// per reachable instruction. Operands are loaded into locals when the run
+/// starts, so patched operands like day 2's work, except for those the
+/// program writes to itself. If the program reaches an instruction after
+/// overwriting one of the other words its arm was generated from, jumps
+/// somewhere that wasn't decoded, or would fault, the module hands the rest of
+/// the run off to the interpreter.
+pub fn transpile(program: &Program) -> String {
+    let instructions = reachable_instructions(program);
+    let mut out = String::new();
+    let words: Vec<String> = program.image().iter().map(|w| w.to_string()).collect();
+
+    writeln!(out, "// Generated by intcode-transpile, do not edit").unwrap();
+    writeln!(
+        out,
+        "#![allow(clippy::all, dead_code, unused_mut, unused_variables, unreachable_code)]"
+    )
+    .unwrap();
+    writeln!(out, "use intcode::IntcodeComputer;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "pub const PROGRAM: [i128; {}] = [{}];",
+        words.len(),
+        words.join(", ")
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    out.push_str(HELPERS);
+    writeln!(out).unwrap();
+
+    // Every instruction reads all three parameters, like the interpreter
+    let written = written_operands(program, &instructions);
+    let code: BTreeSet<usize> = instructions
+        .keys()
+        .flat_map(|address| *address..=address + 3)
+        .filter(|address| instructions.contains_key(address) || !written.contains(address))
+        .collect();
+    let code_list: Vec<String> = code.iter().map(|a| a.to_string()).collect();
+    writeln!(
+        out,
+        "/// Addresses of the words the match arms were generated from"
+    )
+    .unwrap();
+    writeln!(out, "fn is_code(address: usize) -> bool {{").unwrap();
+    if code_list.is_empty() {
+        writeln!(out, "    false").unwrap();
+    } else {
+        writeln!(out, "    matches!(address, {})", code_list.join(" | ")).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+
+    writeln!(
+        out,
+        "/// Runs until the program halts, starting from `m` as memory"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "pub fn run(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>) {{"
+    )
+    .unwrap();
+    for address in code.iter().filter(|a| !written.contains(a)) {
+        writeln!(out, "    let p{} = param(m, {});", address, address).unwrap();
+    }
+    writeln!(out, "    let mut ip: usize = 0;").unwrap();
+    writeln!(out, "    let mut rb: i128 = 0;").unwrap();
+    writeln!(
+        out,
+        "    // Generated words the program has changed, which only matter once an"
+    )
+    .unwrap();
+    writeln!(out, "    // instruction using them is reached").unwrap();
+    writeln!(out, "    let mut stale: Vec<usize> = Vec::new();").unwrap();
+    writeln!(out, "    loop {{").unwrap();
+    writeln!(
+        out,
+        "        if !stale.is_empty() && stale.iter().any(|a| ip <= *a && *a < ip + 4) {{"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "            return fallback(m, inputs, outputs, ip, rb);"
+    )
+    .unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "        match ip {{").unwrap();
+    for (address, instruction) in instructions.iter() {
+        let next = address + instruction_len(instruction.operation);
+        writeln!(out, "            {} => {{", address).unwrap();
+        let body = arm_body(*address, instruction, next, &written);
+        for line in body.lines() {
+            writeln!(out, "                {}", line).unwrap();
+        }
+        writeln!(out, "            }}").unwrap();
+    }
+    writeln!(
+        out,
+        "            _ => return fallback(m, inputs, outputs, ip, rb),"
+    )
+    .unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn arm_body(
+    address: usize,
+    instruction: &IntcodeInstruction,
+    next: usize,
+    written: &BTreeSet<usize>,
+) -> String {
+    let mut body = String::new();
+    if instruction.operation == IntcodeOperation::Halt {
+        body.push_str("return;\n");
+        return body;
+    }
+    let fallback = fallback_expr(address);
+    // Reads and destinations are evaluated for every instruction, like in
+    // `IntcodeComputer::handle_instruction`, since they can grow memory
+    writeln!(
+        body,
+        "let a = {};",
+        read_expr(address, 1, instruction.mode_1, written)
+    )
+    .unwrap();
+    writeln!(
+        body,
+        "let b = {};",
+        read_expr(address, 2, instruction.mode_2, written)
+    )
+    .unwrap();
+    writeln!(
+        body,
+        "let c = {};",
+        dest_expr(address, 3, instruction.mode_3, written)
+    )
+    .unwrap();
+    let write = |body: &mut String, dest: &str, value: &str| {
+        writeln!(body, "if write(m, {}, {}) {{", dest, value).unwrap();
+        writeln!(body, "    stale.push({});", dest).unwrap();
+        writeln!(body, "}}").unwrap();
+        writeln!(body, "ip = {};", next).unwrap();
+    };
+    let checked_dest = |body: &mut String, dest: &str| {
+        writeln!(
+            body,
+            "let dest = or_fallback!(address({}), {});",
+            dest, fallback
+        )
+        .unwrap();
+    };
+    match instruction.operation {
+        IntcodeOperation::Add | IntcodeOperation::Multiply => {
+            let op = match instruction.operation {
+                IntcodeOperation::Add => "checked_add",
+                _ => "checked_mul",
+            };
+            checked_dest(&mut body, "c");
+            writeln!(body, "let value = or_fallback!(a.{}(b), {});", op, fallback).unwrap();
+            write(&mut body, "dest", "value");
+        }
+        IntcodeOperation::LessThan | IntcodeOperation::Equal => {
+            let op = match instruction.operation {
+                IntcodeOperation::LessThan => "<",
+                _ => "==",
+            };
+            checked_dest(&mut body, "c");
+            write(&mut body, "dest", &format!("(a {} b) as i128", op));
+        }
+        IntcodeOperation::Input => {
+            checked_dest(
+                &mut body,
+                &dest_expr(address, 1, instruction.mode_1, written),
+            );
+            writeln!(
+                body,
+                "let value = if !inputs.is_empty() {{ inputs.remove(0) }} else {{ outputs.pop().unwrap_or(0) }};"
+            )
+            .unwrap();
+            write(&mut body, "dest", "value");
+        }
+        IntcodeOperation::Output => {
+            writeln!(body, "outputs.push(a);").unwrap();
+            writeln!(body, "ip = {};", next).unwrap();
+        }
+        IntcodeOperation::JumpIfTrue | IntcodeOperation::JumpIfFalse => {
+            let cond = match instruction.operation {
+                IntcodeOperation::JumpIfTrue => "a != 0",
+                _ => "a == 0",
+            };
+            writeln!(
+                body,
+                "ip = if {} {{ or_fallback!(address(b), {}) }} else {{ {} }};",
+                cond, fallback, next
+            )
+            .unwrap();
+        }
+        IntcodeOperation::RelativeBaseOffset => {
+            writeln!(body, "rb = or_fallback!(rb.checked_add(a), {});", fallback).unwrap();
+            writeln!(body, "ip = {};", next).unwrap();
+        }
+        IntcodeOperation::Halt => {}
+    }
+    body
+}
+
+const HELPERS: &str = "/// Addresses from here on are left to the interpreter, which knows the
+/// machine's memory limit
+const MAX_MEMORY: usize = 1 << 24;
+
+/// Unwraps a value, or returns the result of handing off to the interpreter
+macro_rules! or_fallback {
+    ($value:expr, $fallback:expr) => {
+        match $value {
+            Some(value) => value,
+            None => return $fallback,
+        }
+    };
+}
+
+#[inline(always)]
+fn param(m: &[i128], address: usize) -> i128 {
+    *m.get(address).unwrap_or(&0)
+}
+
+#[inline(always)]
+fn extend(m: &mut Vec<i128>, len: usize) {
+    if m.len() < len {
+        grow(m, len);
+    }
+}
+
+/// Kept out of line so the common case of memory already being big enough
+/// stays small
+#[cold]
+#[inline(never)]
+fn grow(m: &mut Vec<i128>, len: usize) {
+    m.resize(len, 0);
+}
+
+/// Address the generated code can use directly, if any
+#[inline(always)]
+fn address(arg: i128) -> Option<usize> {
+    if arg < 0 || arg >= MAX_MEMORY as i128 {
+        None
+    } else {
+        Some(arg as usize)
+    }
+}
+
+#[inline(always)]
+fn position(m: &mut Vec<i128>, arg: i128) -> Option<i128> {
+    if arg < 0 {
+        return Some(0);
+    }
+    let address = address(arg)?;
+    extend(m, address + 1);
+    Some(m[address])
+}
+
+#[inline(always)]
+fn relative(m: &mut Vec<i128>, rb: i128, arg: i128, dest: bool) -> Option<i128> {
+    let target = rb.checked_add(arg)?;
+    let address = address(target)?;
+    extend(m, address + 1);
+    if dest {
+        Some(target)
+    } else {
+        Some(m[address])
+    }
+}
+
+/// Writes a value, returning true if it changed one of the generated words
+#[inline(always)]
+fn write(m: &mut Vec<i128>, address: usize, value: i128) -> bool {
+    extend(m, address + 1);
+    let changed = m[address] != value;
+    m[address] = value;
+    changed && is_code(address)
+}
+
+/// Finishes the run on the interpreter from the given state
+#[cold]
+#[inline(never)]
+fn fallback(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>, ip: usize, rb: i128) {
+    let mut computer = IntcodeComputer::new(std::mem::take(m), std::mem::take(inputs), false);
+    computer.outputs = std::mem::take(outputs);
+    computer.index = ip;
+    computer.relative_base = rb;
+    computer.run();
+    *m = computer.intcodes.to_vec();
+    *inputs = computer.inputs;
+    *outputs = computer.outputs;
+}
+";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reachable_instructions() {
+        // Jumps over the data at 3 and 4 to the halt at 5
+        let program = Program::new(vec![1105, 1, 5, 1, 2, 99]);
+        let found = reachable_instructions(&program);
+        assert_eq!(found.keys().copied().collect::<Vec<usize>>(), vec![0, 3, 5]);
+    }
+
+    #[test]
+    fn test_transpile_arms() {
+        let source = transpile(&Program::new(vec![1101, 2, 3, 0, 4, 0, 99]));
+        assert!(source.contains("pub const PROGRAM: [i128; 7] = [1101, 2, 3, 0, 4, 0, 99];"));
+        assert!(source.contains("            0 => {"));
+        assert!(source.contains("            4 => {"));
+        assert!(source.contains("            6 => {\n                return;"));
+        assert!(source.contains("matches!(address, 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9)"));
+        assert!(source.contains("    let p1 = param(m, 1);\n"));
+        assert!(source.contains(
+            "let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 0, rb));"
+        ));
+
+        // An operand the program writes to is read from memory instead
+        let source = transpile(&Program::new(vec![1101, 2, 3, 5, 104, 0, 99]));
+        assert!(!source.contains("let p5 ="));
+        assert!(source.contains("let a = param(m, 5);"));
+    }
+}
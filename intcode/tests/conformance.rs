@@ -0,0 +1,582 @@
+use intcode::{
+    optimize, run_amplifiers, AddressingPolicy, AmplifierWiring, InputSource, IntcodeComputer,
+    IntcodeObserver, IntcodeOperation, PhaseObjective, PhaseSearch, Program,
+};
+use std::sync::{Arc, Mutex};
+
+// Modules generated by `intcode-transpile`, shared with the transpiler tests
+#[rustfmt::skip]
+#[path = "transpiled/compare.rs"]
+mod compare;
+#[rustfmt::skip]
+#[path = "transpiled/quine.rs"]
+mod quine;
+#[rustfmt::skip]
+#[path = "transpiled/selfmod.rs"]
+mod selfmod;
+
+struct Case {
+    name: &'static str,
+    program: Vec<i128>,
+    inputs: Vec<i128>,
+    /// Memory once the program halts, if the case checks it. Memory past the
+    /// end reads as 0, so trailing zeros are ignored.
+    memory: Option<Vec<i128>>,
+    outputs: Vec<i128>,
+}
+
+fn case(
+    name: &'static str,
+    program: &[i128],
+    inputs: &[i128],
+    memory: Option<&[i128]>,
+    outputs: &[i128],
+) -> Case {
+    Case {
+        name,
+        program: program.to_vec(),
+        inputs: inputs.to_vec(),
+        memory: memory.map(|memory| memory.to_vec()),
+        outputs: outputs.to_vec(),
+    }
+}
+
+const COMPARE: [i128; 47] = [
+    3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+    1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20, 1105,
+    1, 46, 98, 99,
+];
+const QUINE: [i128; 16] = [
+    109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+];
+
+/// Example programs from the puzzles
+fn examples() -> Vec<Case> {
+    let mut cases = vec![
+        case(
+            "day 2 add",
+            &[1, 0, 0, 0, 99],
+            &[],
+            Some(&[2, 0, 0, 0, 99]),
+            &[],
+        ),
+        case(
+            "day 2 multiply",
+            &[2, 3, 0, 3, 99],
+            &[],
+            Some(&[2, 3, 0, 6, 99]),
+            &[],
+        ),
+        case(
+            "day 2 multiply past code",
+            &[2, 4, 4, 5, 99, 0],
+            &[],
+            Some(&[2, 4, 4, 5, 99, 9801]),
+            &[],
+        ),
+        case(
+            "day 2 overwrite halt",
+            &[1, 1, 1, 4, 99, 5, 6, 0, 99],
+            &[],
+            Some(&[30, 1, 1, 4, 2, 5, 6, 0, 99]),
+            &[],
+        ),
+        case(
+            "day 2 walkthrough",
+            &[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50],
+            &[],
+            Some(&[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]),
+            &[],
+        ),
+        case(
+            "day 5 echo",
+            &[3, 0, 4, 0, 99],
+            &[17],
+            Some(&[17, 0, 4, 0, 99]),
+            &[17],
+        ),
+        case(
+            "day 5 parameter modes",
+            &[1002, 4, 3, 4, 33],
+            &[],
+            Some(&[1002, 4, 3, 4, 99]),
+            &[],
+        ),
+        case(
+            "day 5 negative immediate",
+            &[1101, 100, -1, 4, 0],
+            &[],
+            Some(&[1101, 100, -1, 4, 99]),
+            &[],
+        ),
+        case("day 9 quine", &QUINE, &[], None, &QUINE),
+        case(
+            "day 9 sixteen digits",
+            &[1102, 34915192, 34915192, 7, 4, 7, 99, 0],
+            &[],
+            Some(&[1102, 34915192, 34915192, 7, 4, 7, 99, 1219070632396864]),
+            &[1219070632396864],
+        ),
+        case(
+            "day 9 large immediate",
+            &[104, 1125899906842624, 99],
+            &[],
+            Some(&[104, 1125899906842624, 99]),
+            &[1125899906842624],
+        ),
+    ];
+    type Compare = (&'static str, [i128; 11], fn(i128) -> bool);
+    let compares: [Compare; 4] = [
+        (
+            "day 5 equal position",
+            [3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8],
+            |n| n == 8,
+        ),
+        (
+            "day 5 less position",
+            [3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8],
+            |n| n < 8,
+        ),
+        (
+            "day 5 equal immediate",
+            [3, 3, 1108, -1, 8, 3, 4, 3, 99, 0, 0],
+            |n| n == 8,
+        ),
+        (
+            "day 5 less immediate",
+            [3, 3, 1107, -1, 8, 3, 4, 3, 99, 0, 0],
+            |n| n < 8,
+        ),
+    ];
+    for (name, program, expected) in compares.iter() {
+        for input in [7, 8, 9].iter() {
+            cases.push(case(
+                name,
+                program,
+                &[*input],
+                None,
+                &[expected(*input) as i128],
+            ));
+        }
+    }
+    let jumps: [(&'static str, &[i128]); 2] = [
+        (
+            "day 5 jump position",
+            &[3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9],
+        ),
+        (
+            "day 5 jump immediate",
+            &[3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1],
+        ),
+    ];
+    for (name, program) in jumps.iter() {
+        cases.push(case(name, program, &[0], None, &[0]));
+        cases.push(case(name, program, &[5], None, &[1]));
+    }
+    for (input, output) in [(7, 999), (8, 1000), (9, 1001)].iter() {
+        cases.push(case("day 5 compare", &COMPARE, &[*input], None, &[*output]));
+    }
+    cases
+}
+
+/// Edge cases for each opcode and parameter mode
+fn edge_cases() -> Vec<Case> {
+    vec![
+        case(
+            "add position",
+            &[1, 5, 6, 7, 99, 3, 4, 0],
+            &[],
+            Some(&[1, 5, 6, 7, 99, 3, 4, 7]),
+            &[],
+        ),
+        case(
+            "add relative",
+            &[109, 7, 22201, 0, 1, 2, 99, 3, 4, 0],
+            &[],
+            Some(&[109, 7, 22201, 0, 1, 2, 99, 3, 4, 7]),
+            &[],
+        ),
+        case(
+            "multiply negative",
+            &[1102, -3, 7, 5, 99, 0],
+            &[],
+            Some(&[1102, -3, 7, 5, 99, -21]),
+            &[],
+        ),
+        case(
+            "multiply mixed modes",
+            &[1002, 5, -2, 6, 99, 4, 0],
+            &[],
+            Some(&[1002, 5, -2, 6, 99, 4, -8]),
+            &[],
+        ),
+        case(
+            "multiply past 64 bits",
+            &[1102, 4611686018427387904, 4, 7, 4, 7, 99, 0],
+            &[],
+            Some(&[
+                1102,
+                4611686018427387904,
+                4,
+                7,
+                4,
+                7,
+                99,
+                18446744073709551616,
+            ]),
+            &[18446744073709551616],
+        ),
+        case(
+            "input position",
+            &[3, 3, 99, 0],
+            &[42],
+            Some(&[3, 3, 99, 42]),
+            &[],
+        ),
+        case(
+            "input relative",
+            &[109, 5, 203, 1, 99, 0, 0],
+            &[5],
+            Some(&[109, 5, 203, 1, 99, 0, 5]),
+            &[],
+        ),
+        case(
+            "inputs in order",
+            &[3, 11, 3, 12, 7, 11, 12, 13, 4, 13, 99, 0, 0, 0],
+            &[2, 5],
+            Some(&[3, 11, 3, 12, 7, 11, 12, 13, 4, 13, 99, 2, 5, 1]),
+            &[1],
+        ),
+        case(
+            "output modes",
+            &[4, 9, 104, 8, 109, 2, 204, 7, 99, 11],
+            &[],
+            Some(&[4, 9, 104, 8, 109, 2, 204, 7, 99, 11]),
+            &[11, 8, 11],
+        ),
+        case(
+            "jump if true",
+            &[1105, 0, 99, 1105, 1, 7, 99, 104, 1, 99],
+            &[],
+            Some(&[1105, 0, 99, 1105, 1, 7, 99, 104, 1, 99]),
+            &[1],
+        ),
+        case(
+            "jump if true relative target",
+            &[109, 7, 2105, 1, 3, 99, 104, 2, 99, 0, 6],
+            &[],
+            Some(&[109, 7, 2105, 1, 3, 99, 104, 2, 99, 0, 6]),
+            &[2],
+        ),
+        case(
+            "jump if false",
+            &[1106, 1, 99, 6, 10, 11, 99, 104, 3, 99, 0, 7],
+            &[],
+            Some(&[1106, 1, 99, 6, 10, 11, 99, 104, 3, 99, 0, 7]),
+            &[3],
+        ),
+        case(
+            "less than",
+            &[1107, 1, 2, 9, 1107, 2, 1, 10, 99, -1, -1],
+            &[],
+            Some(&[1107, 1, 2, 9, 1107, 2, 1, 10, 99, 1, 0]),
+            &[],
+        ),
+        case(
+            "equals",
+            &[1108, 5, 5, 9, 8, 9, 10, 10, 99, 0, 4],
+            &[],
+            Some(&[1108, 5, 5, 9, 8, 9, 10, 10, 99, 1, 0]),
+            &[],
+        ),
+        case(
+            "relative base negative offset",
+            &[109, 10, 109, -7, 204, -1, 99],
+            &[],
+            Some(&[109, 10, 109, -7, 204, -1, 99]),
+            &[109],
+        ),
+        case(
+            "relative destination",
+            &[109, 8, 21101, 2, 3, 0, 99, 0, 0],
+            &[],
+            Some(&[109, 8, 21101, 2, 3, 0, 99, 0, 5]),
+            &[],
+        ),
+        case("read past end", &[4, 10, 99], &[], Some(&[4, 10, 99]), &[0]),
+        case(
+            "write past end",
+            &[1101, 1, 2, 6, 99],
+            &[],
+            Some(&[1101, 1, 2, 6, 99, 0, 3]),
+            &[],
+        ),
+        case("halt", &[99], &[], Some(&[99]), &[]),
+    ]
+}
+
+struct Outcome {
+    /// `None` for variants that rewrite the program
+    memory: Option<Vec<i128>>,
+    outputs: Vec<i128>,
+}
+
+impl Outcome {
+    fn from_computer(computer: &IntcodeComputer) -> Outcome {
+        Outcome {
+            memory: Some(computer.intcodes.to_vec()),
+            outputs: computer.outputs.clone(),
+        }
+    }
+}
+
+type Variant = fn(&[i128], &[i128]) -> Outcome;
+
+fn run_to_halt(program: &[i128], inputs: &[i128]) -> Outcome {
+    let mut computer = IntcodeComputer::new(program.to_vec(), inputs.to_vec(), false);
+    computer.run();
+    Outcome::from_computer(&computer)
+}
+
+fn pause_on_output(program: &[i128], inputs: &[i128]) -> Outcome {
+    let mut computer = IntcodeComputer::new(program.to_vec(), inputs.to_vec(), true);
+    while computer.run() != IntcodeOperation::Halt {}
+    Outcome::from_computer(&computer)
+}
+
+fn single_step(program: &[i128], inputs: &[i128]) -> Outcome {
+    let mut computer = IntcodeComputer::new(program.to_vec(), inputs.to_vec(), false);
+    while computer.step() != Some(IntcodeOperation::Halt) {}
+    Outcome::from_computer(&computer)
+}
+
+fn strict(program: &[i128], inputs: &[i128]) -> Outcome {
+    let mut computer = IntcodeComputer::new(program.to_vec(), inputs.to_vec(), false);
+    computer.set_addressing(AddressingPolicy::Strict { limit: 1 << 16 });
+    computer.try_run().unwrap();
+    Outcome::from_computer(&computer)
+}
+
+struct Queue(Vec<i128>);
+
+impl InputSource for Queue {
+    fn next_input(&mut self, _step: usize) -> Option<i128> {
+        match self.0.is_empty() {
+            true => None,
+            false => Some(self.0.remove(0)),
+        }
+    }
+}
+
+fn input_source(program: &[i128], inputs: &[i128]) -> Outcome {
+    let mut computer = IntcodeComputer::new(program.to_vec(), vec![], false);
+    computer.set_input_source(Arc::new(Mutex::new(Queue(inputs.to_vec()))));
+    computer.run();
+    Outcome::from_computer(&computer)
+}
+
+/// Rebuilds memory and outputs from observer events alone
+#[derive(Default)]
+struct Tracer {
+    memory: Vec<i128>,
+    outputs: Vec<i128>,
+}
+
+impl IntcodeObserver for Tracer {
+    fn on_memory_write(&mut self, address: usize, _old: i128, new: i128) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = new;
+    }
+
+    fn on_output(&mut self, value: i128) {
+        self.outputs.push(value);
+    }
+}
+
+fn observed(program: &[i128], inputs: &[i128]) -> Outcome {
+    let tracer = Arc::new(Mutex::new(Tracer {
+        memory: program.to_vec(),
+        outputs: Vec::new(),
+    }));
+    let mut computer = IntcodeComputer::new(program.to_vec(), inputs.to_vec(), false);
+    computer.attach_observer(tracer.clone());
+    computer.run();
+    let tracer = tracer.lock().unwrap();
+    Outcome {
+        memory: Some(tracer.memory.clone()),
+        outputs: tracer.outputs.clone(),
+    }
+}
+
+fn optimized(program: &[i128], inputs: &[i128]) -> Outcome {
+    let optimized = optimize(&Program::new(program.to_vec()));
+    let mut computer = optimized.program.spawn(inputs.to_vec(), false);
+    computer.run();
+    Outcome {
+        memory: None,
+        outputs: computer.outputs,
+    }
+}
+
+const VARIANTS: [(&str, Variant); 7] = [
+    ("run", run_to_halt),
+    ("pause on output", pause_on_output),
+    ("step", single_step),
+    ("strict addressing", strict),
+    ("input source", input_source),
+    ("observer", observed),
+    ("optimized", optimized),
+];
+
+fn trimmed(memory: &[i128]) -> &[i128] {
+    let end = memory.iter().rposition(|v| *v != 0).map_or(0, |n| n + 1);
+    &memory[..end]
+}
+
+fn assert_case(variant: &str, case: &Case, outcome: Outcome) {
+    assert_eq!(
+        outcome.outputs, case.outputs,
+        "outputs of {:?} with inputs {:?} on {}",
+        case.name, case.inputs, variant
+    );
+    if let (Some(expected), Some(memory)) = (&case.memory, &outcome.memory) {
+        assert_eq!(
+            trimmed(memory),
+            trimmed(expected),
+            "memory of {:?} on {}",
+            case.name,
+            variant
+        );
+    }
+}
+
+fn assert_conforms(cases: &[Case]) {
+    for (variant, run) in VARIANTS.iter() {
+        for case in cases.iter() {
+            assert_case(variant, case, run(&case.program, &case.inputs));
+        }
+    }
+}
+
+#[test]
+fn test_examples() {
+    assert_conforms(&examples());
+}
+
+#[test]
+fn test_edge_cases() {
+    assert_conforms(&edge_cases());
+}
+
+#[test]
+fn test_transpiled() {
+    type Run = fn(&mut Vec<i128>, &mut Vec<i128>, &mut Vec<i128>);
+    let fixtures: [(&[i128], Run); 3] = [
+        (&compare::PROGRAM, compare::run),
+        (&quine::PROGRAM, quine::run),
+        (&selfmod::PROGRAM, selfmod::run),
+    ];
+    let mut checked = 0;
+    for case in examples().iter().chain(edge_cases().iter()) {
+        for (program, run) in fixtures.iter() {
+            if case.program.as_slice() != *program {
+                continue;
+            }
+            let (mut memory, mut inputs, mut outputs) =
+                (case.program.clone(), case.inputs.clone(), Vec::new());
+            run(&mut memory, &mut inputs, &mut outputs);
+            let outcome = Outcome {
+                memory: Some(memory),
+                outputs,
+            };
+            assert_case("transpiled", case, outcome);
+            checked += 1;
+        }
+    }
+    assert_eq!(checked, 5);
+}
+
+#[test]
+fn test_amplifier_examples() {
+    let serial: [(&[i128], [i128; 5], i128); 3] = [
+        (
+            &[
+                3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+            ],
+            [4, 3, 2, 1, 0],
+            43210,
+        ),
+        (
+            &[
+                3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4,
+                23, 99, 0, 0,
+            ],
+            [0, 1, 2, 3, 4],
+            54321,
+        ),
+        (
+            &[
+                3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7, 33,
+                1, 33, 31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0,
+            ],
+            [1, 0, 4, 3, 2],
+            65210,
+        ),
+    ];
+    let feedback: [(&[i128], [i128; 5], i128); 2] = [
+        (
+            &[
+                3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28,
+                -1, 28, 1005, 28, 6, 99, 0, 0, 5,
+            ],
+            [9, 8, 7, 6, 5],
+            139629729,
+        ),
+        (
+            &[
+                3, 52, 1001, 52, -5, 52, 3, 53, 1, 52, 56, 54, 1007, 54, 5, 55, 1005, 55, 26, 1001,
+                54, -5, 54, 1105, 1, 12, 1, 53, 54, 53, 1008, 54, 0, 55, 1001, 55, 1, 55, 2, 53,
+                55, 53, 4, 53, 1001, 56, -1, 56, 1005, 56, 6, 99, 0, 0, 0, 0, 10,
+            ],
+            [9, 7, 8, 5, 6],
+            18216,
+        ),
+    ];
+
+    for (program, phases, signal) in serial.iter() {
+        for (variant, run) in VARIANTS.iter() {
+            let chained = phases
+                .iter()
+                .fold(0, |input, phase| run(program, &[*phase, input]).outputs[0]);
+            assert_eq!(chained, *signal, "phases {:?} on {}", phases, variant);
+        }
+        let program = Program::new(program.to_vec());
+        assert_eq!(
+            run_amplifiers(&program, phases, AmplifierWiring::Serial, 1_000_000),
+            Some(*signal)
+        );
+        let best = PhaseSearch::new(&program, 5, (0..5).collect(), AmplifierWiring::Serial)
+            .run(PhaseObjective::Maximize)
+            .unwrap();
+        assert_eq!(
+            (best.phases.as_slice(), best.signal),
+            (&phases[..], *signal)
+        );
+    }
+    for (program, phases, signal) in feedback.iter() {
+        let program = Program::new(program.to_vec());
+        assert_eq!(
+            run_amplifiers(&program, phases, AmplifierWiring::Feedback, 1_000_000),
+            Some(*signal)
+        );
+        let best = PhaseSearch::new(&program, 5, (5..10).collect(), AmplifierWiring::Feedback)
+            .run(PhaseObjective::Maximize)
+            .unwrap();
+        assert_eq!(
+            (best.phases.as_slice(), best.signal),
+            (&phases[..], *signal)
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use intcode::{check_case, FuzzBudget, FuzzCase};
+use std::fs;
+use std::path::Path;
+
+/// Every crash `intcode-fuzz` has found, minimized, should now run without
+/// panicking
+#[test]
+fn test_corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fuzz_corpus");
+    let mut checked = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let case = FuzzCase::load(&path).unwrap();
+        assert_eq!(
+            check_case(&case, FuzzBudget::default()),
+            Ok(()),
+            "{}",
+            path.display()
+        );
+        checked += 1;
+    }
+    assert!(checked > 0);
+}
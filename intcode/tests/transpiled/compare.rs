@@ -0,0 +1,277 @@
+// Generated by intcode-transpile, do not edit
+#![allow(clippy::all, dead_code, unused_mut, unused_variables, unreachable_code)]
+use intcode::IntcodeComputer;
+
+pub const PROGRAM: [i128; 47] = [3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20, 1105, 1, 46, 98, 99];
+
+/// Addresses from here on are left to the interpreter, which knows the
+/// machine's memory limit
+const MAX_MEMORY: usize = 1 << 24;
+
+/// Unwraps a value, or returns the result of handing off to the interpreter
+macro_rules! or_fallback {
+    ($value:expr, $fallback:expr) => {
+        match $value {
+            Some(value) => value,
+            None => return $fallback,
+        }
+    };
+}
+
+#[inline(always)]
+fn param(m: &[i128], address: usize) -> i128 {
+    *m.get(address).unwrap_or(&0)
+}
+
+#[inline(always)]
+fn extend(m: &mut Vec<i128>, len: usize) {
+    if m.len() < len {
+        grow(m, len);
+    }
+}
+
+/// Kept out of line so the common case of memory already being big enough
+/// stays small
+#[cold]
+#[inline(never)]
+fn grow(m: &mut Vec<i128>, len: usize) {
+    m.resize(len, 0);
+}
+
+/// Address the generated code can use directly, if any
+#[inline(always)]
+fn address(arg: i128) -> Option<usize> {
+    if arg < 0 || arg >= MAX_MEMORY as i128 {
+        None
+    } else {
+        Some(arg as usize)
+    }
+}
+
+#[inline(always)]
+fn position(m: &mut Vec<i128>, arg: i128) -> Option<i128> {
+    if arg < 0 {
+        return Some(0);
+    }
+    let address = address(arg)?;
+    extend(m, address + 1);
+    Some(m[address])
+}
+
+#[inline(always)]
+fn relative(m: &mut Vec<i128>, rb: i128, arg: i128, dest: bool) -> Option<i128> {
+    let target = rb.checked_add(arg)?;
+    let address = address(target)?;
+    extend(m, address + 1);
+    if dest {
+        Some(target)
+    } else {
+        Some(m[address])
+    }
+}
+
+/// Writes a value, returning true if it changed one of the generated words
+#[inline(always)]
+fn write(m: &mut Vec<i128>, address: usize, value: i128) -> bool {
+    extend(m, address + 1);
+    let changed = m[address] != value;
+    m[address] = value;
+    changed && is_code(address)
+}
+
+/// Finishes the run on the interpreter from the given state
+#[cold]
+#[inline(never)]
+fn fallback(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>, ip: usize, rb: i128) {
+    let mut computer = IntcodeComputer::new(std::mem::take(m), std::mem::take(inputs), false);
+    computer.outputs = std::mem::take(outputs);
+    computer.index = ip;
+    computer.relative_base = rb;
+    computer.run();
+    *m = computer.intcodes.to_vec();
+    *inputs = computer.inputs;
+    *outputs = computer.outputs;
+}
+
+/// Addresses of the words the match arms were generated from
+fn is_code(address: usize) -> bool {
+    matches!(address, 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | 11 | 12 | 13 | 14 | 15 | 16 | 17 | 18 | 19 | 22 | 23 | 24 | 25 | 26 | 27 | 28 | 29 | 30 | 31 | 32 | 33 | 34 | 35 | 36 | 37 | 38 | 39 | 40 | 41 | 42 | 43 | 44 | 45 | 46 | 47 | 48 | 49)
+}
+
+/// Runs until the program halts, starting from `m` as memory
+pub fn run(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>) {
+    let p0 = param(m, 0);
+    let p1 = param(m, 1);
+    let p2 = param(m, 2);
+    let p3 = param(m, 3);
+    let p4 = param(m, 4);
+    let p5 = param(m, 5);
+    let p6 = param(m, 6);
+    let p7 = param(m, 7);
+    let p8 = param(m, 8);
+    let p9 = param(m, 9);
+    let p10 = param(m, 10);
+    let p11 = param(m, 11);
+    let p12 = param(m, 12);
+    let p13 = param(m, 13);
+    let p14 = param(m, 14);
+    let p15 = param(m, 15);
+    let p16 = param(m, 16);
+    let p17 = param(m, 17);
+    let p18 = param(m, 18);
+    let p19 = param(m, 19);
+    let p22 = param(m, 22);
+    let p23 = param(m, 23);
+    let p24 = param(m, 24);
+    let p25 = param(m, 25);
+    let p26 = param(m, 26);
+    let p27 = param(m, 27);
+    let p28 = param(m, 28);
+    let p29 = param(m, 29);
+    let p30 = param(m, 30);
+    let p31 = param(m, 31);
+    let p32 = param(m, 32);
+    let p33 = param(m, 33);
+    let p34 = param(m, 34);
+    let p35 = param(m, 35);
+    let p36 = param(m, 36);
+    let p37 = param(m, 37);
+    let p38 = param(m, 38);
+    let p39 = param(m, 39);
+    let p40 = param(m, 40);
+    let p41 = param(m, 41);
+    let p42 = param(m, 42);
+    let p43 = param(m, 43);
+    let p44 = param(m, 44);
+    let p45 = param(m, 45);
+    let p46 = param(m, 46);
+    let p47 = param(m, 47);
+    let p48 = param(m, 48);
+    let p49 = param(m, 49);
+    let mut ip: usize = 0;
+    let mut rb: i128 = 0;
+    // Generated words the program has changed, which only matter once an
+    // instruction using them is reached
+    let mut stale: Vec<usize> = Vec::new();
+    loop {
+        if !stale.is_empty() && stale.iter().any(|a| ip <= *a && *a < ip + 4) {
+            return fallback(m, inputs, outputs, ip, rb);
+        }
+        match ip {
+            0 => {
+                let a = or_fallback!(position(m, p1), fallback(m, inputs, outputs, 0, rb));
+                let b = or_fallback!(position(m, p2), fallback(m, inputs, outputs, 0, rb));
+                let c = p3;
+                let dest = or_fallback!(address(p1), fallback(m, inputs, outputs, 0, rb));
+                let value = if !inputs.is_empty() { inputs.remove(0) } else { outputs.pop().unwrap_or(0) };
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 2;
+            }
+            2 => {
+                let a = or_fallback!(position(m, p3), fallback(m, inputs, outputs, 2, rb));
+                let b = p4;
+                let c = p5;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 2, rb));
+                if write(m, dest, (a == b) as i128) {
+                    stale.push(dest);
+                }
+                ip = 6;
+            }
+            6 => {
+                let a = or_fallback!(position(m, p7), fallback(m, inputs, outputs, 6, rb));
+                let b = p8;
+                let c = p9;
+                ip = if a != 0 { or_fallback!(address(b), fallback(m, inputs, outputs, 6, rb)) } else { 9 };
+            }
+            9 => {
+                let a = p10;
+                let b = or_fallback!(position(m, p11), fallback(m, inputs, outputs, 9, rb));
+                let c = p12;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 9, rb));
+                if write(m, dest, (a < b) as i128) {
+                    stale.push(dest);
+                }
+                ip = 13;
+            }
+            13 => {
+                let a = or_fallback!(position(m, p14), fallback(m, inputs, outputs, 13, rb));
+                let b = p15;
+                let c = p16;
+                ip = if a == 0 { or_fallback!(address(b), fallback(m, inputs, outputs, 13, rb)) } else { 16 };
+            }
+            16 => {
+                let a = p17;
+                let b = p18;
+                let c = p19;
+                ip = if a == 0 { or_fallback!(address(b), fallback(m, inputs, outputs, 16, rb)) } else { 19 };
+            }
+            22 => {
+                let a = or_fallback!(position(m, p23), fallback(m, inputs, outputs, 22, rb));
+                let b = p24;
+                let c = p25;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 22, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 22, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 26;
+            }
+            26 => {
+                let a = or_fallback!(position(m, p27), fallback(m, inputs, outputs, 26, rb));
+                let b = or_fallback!(position(m, p28), fallback(m, inputs, outputs, 26, rb));
+                let c = p29;
+                outputs.push(a);
+                ip = 28;
+            }
+            28 => {
+                let a = p29;
+                let b = p30;
+                let c = p31;
+                ip = if a != 0 { or_fallback!(address(b), fallback(m, inputs, outputs, 28, rb)) } else { 31 };
+            }
+            31 => {
+                let a = p32;
+                let b = or_fallback!(position(m, p33), fallback(m, inputs, outputs, 31, rb));
+                let c = p34;
+                outputs.push(a);
+                ip = 33;
+            }
+            33 => {
+                let a = p34;
+                let b = p35;
+                let c = p36;
+                ip = if a != 0 { or_fallback!(address(b), fallback(m, inputs, outputs, 33, rb)) } else { 36 };
+            }
+            36 => {
+                let a = p37;
+                let b = p38;
+                let c = p39;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 36, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 36, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 40;
+            }
+            40 => {
+                let a = or_fallback!(position(m, p41), fallback(m, inputs, outputs, 40, rb));
+                let b = or_fallback!(position(m, p42), fallback(m, inputs, outputs, 40, rb));
+                let c = p43;
+                outputs.push(a);
+                ip = 42;
+            }
+            42 => {
+                let a = p43;
+                let b = p44;
+                let c = p45;
+                ip = if a != 0 { or_fallback!(address(b), fallback(m, inputs, outputs, 42, rb)) } else { 45 };
+            }
+            46 => {
+                return;
+            }
+            _ => return fallback(m, inputs, outputs, ip, rb),
+        }
+    }
+}
@@ -0,0 +1,658 @@
+// Generated by intcode-transpile, do not edit
+#![allow(clippy::all, dead_code, unused_mut, unused_variables, unreachable_code)]
+use intcode::IntcodeComputer;
+
+pub const PROGRAM: [i128; 157] = [1, 0, 0, 3, 1, 1, 2, 3, 1, 3, 4, 3, 1, 5, 0, 3, 2, 1, 13, 19, 1, 9, 19, 23, 1, 6, 23, 27, 2, 27, 9, 31, 2, 6, 31, 35, 1, 5, 35, 39, 1, 10, 39, 43, 1, 43, 13, 47, 1, 47, 9, 51, 1, 51, 9, 55, 1, 55, 9, 59, 2, 9, 59, 63, 2, 9, 63, 67, 1, 5, 67, 71, 2, 13, 71, 75, 1, 6, 75, 79, 1, 10, 79, 83, 2, 6, 83, 87, 1, 87, 5, 91, 1, 91, 9, 95, 1, 95, 10, 99, 2, 9, 99, 103, 1, 5, 103, 107, 1, 5, 107, 111, 2, 111, 10, 115, 1, 6, 115, 119, 2, 10, 119, 123, 1, 6, 123, 127, 1, 127, 5, 131, 2, 9, 131, 135, 1, 5, 135, 139, 1, 139, 10, 143, 1, 143, 2, 147, 1, 147, 5, 0, 99, 2, 0, 14, 0];
+
+/// Addresses from here on are left to the interpreter, which knows the
+/// machine's memory limit
+const MAX_MEMORY: usize = 1 << 24;
+
+/// Unwraps a value, or returns the result of handing off to the interpreter
+macro_rules! or_fallback {
+    ($value:expr, $fallback:expr) => {
+        match $value {
+            Some(value) => value,
+            None => return $fallback,
+        }
+    };
+}
+
+#[inline(always)]
+fn param(m: &[i128], address: usize) -> i128 {
+    *m.get(address).unwrap_or(&0)
+}
+
+#[inline(always)]
+fn extend(m: &mut Vec<i128>, len: usize) {
+    if m.len() < len {
+        grow(m, len);
+    }
+}
+
+/// Kept out of line so the common case of memory already being big enough
+/// stays small
+#[cold]
+#[inline(never)]
+fn grow(m: &mut Vec<i128>, len: usize) {
+    m.resize(len, 0);
+}
+
+/// Address the generated code can use directly, if any
+#[inline(always)]
+fn address(arg: i128) -> Option<usize> {
+    if arg < 0 || arg >= MAX_MEMORY as i128 {
+        None
+    } else {
+        Some(arg as usize)
+    }
+}
+
+#[inline(always)]
+fn position(m: &mut Vec<i128>, arg: i128) -> Option<i128> {
+    if arg < 0 {
+        return Some(0);
+    }
+    let address = address(arg)?;
+    extend(m, address + 1);
+    Some(m[address])
+}
+
+#[inline(always)]
+fn relative(m: &mut Vec<i128>, rb: i128, arg: i128, dest: bool) -> Option<i128> {
+    let target = rb.checked_add(arg)?;
+    let address = address(target)?;
+    extend(m, address + 1);
+    if dest {
+        Some(target)
+    } else {
+        Some(m[address])
+    }
+}
+
+/// Writes a value, returning true if it changed one of the generated words
+#[inline(always)]
+fn write(m: &mut Vec<i128>, address: usize, value: i128) -> bool {
+    extend(m, address + 1);
+    let changed = m[address] != value;
+    m[address] = value;
+    changed && is_code(address)
+}
+
+/// Finishes the run on the interpreter from the given state
+#[cold]
+#[inline(never)]
+fn fallback(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>, ip: usize, rb: i128) {
+    let mut computer = IntcodeComputer::new(std::mem::take(m), std::mem::take(inputs), false);
+    computer.outputs = std::mem::take(outputs);
+    computer.index = ip;
+    computer.relative_base = rb;
+    computer.run();
+    *m = computer.intcodes.to_vec();
+    *inputs = computer.inputs;
+    *outputs = computer.outputs;
+}
+
+/// Addresses of the words the match arms were generated from
+fn is_code(address: usize) -> bool {
+    matches!(address, 0 | 1 | 2 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | 11 | 12 | 13 | 14 | 15 | 16 | 17 | 18 | 20 | 21 | 22 | 24 | 25 | 26 | 28 | 29 | 30 | 32 | 33 | 34 | 36 | 37 | 38 | 40 | 41 | 42 | 44 | 45 | 46 | 48 | 49 | 50 | 52 | 53 | 54 | 56 | 57 | 58 | 60 | 61 | 62 | 64 | 65 | 66 | 68 | 69 | 70 | 72 | 73 | 74 | 76 | 77 | 78 | 80 | 81 | 82 | 84 | 85 | 86 | 88 | 89 | 90 | 92 | 93 | 94 | 96 | 97 | 98 | 100 | 101 | 102 | 104 | 105 | 106 | 108 | 109 | 110 | 112 | 113 | 114 | 116 | 117 | 118 | 120 | 121 | 122 | 124 | 125 | 126 | 128 | 129 | 130 | 132 | 133 | 134 | 136 | 137 | 138 | 140 | 141 | 142 | 144 | 145 | 146 | 148 | 149 | 150 | 151 | 152 | 153 | 154 | 155)
+}
+
+/// Runs until the program halts, starting from `m` as memory
+pub fn run(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>) {
+    let p1 = param(m, 1);
+    let p2 = param(m, 2);
+    let p4 = param(m, 4);
+    let p5 = param(m, 5);
+    let p6 = param(m, 6);
+    let p7 = param(m, 7);
+    let p8 = param(m, 8);
+    let p9 = param(m, 9);
+    let p10 = param(m, 10);
+    let p11 = param(m, 11);
+    let p12 = param(m, 12);
+    let p13 = param(m, 13);
+    let p14 = param(m, 14);
+    let p15 = param(m, 15);
+    let p16 = param(m, 16);
+    let p17 = param(m, 17);
+    let p18 = param(m, 18);
+    let p20 = param(m, 20);
+    let p21 = param(m, 21);
+    let p22 = param(m, 22);
+    let p24 = param(m, 24);
+    let p25 = param(m, 25);
+    let p26 = param(m, 26);
+    let p28 = param(m, 28);
+    let p29 = param(m, 29);
+    let p30 = param(m, 30);
+    let p32 = param(m, 32);
+    let p33 = param(m, 33);
+    let p34 = param(m, 34);
+    let p36 = param(m, 36);
+    let p37 = param(m, 37);
+    let p38 = param(m, 38);
+    let p40 = param(m, 40);
+    let p41 = param(m, 41);
+    let p42 = param(m, 42);
+    let p44 = param(m, 44);
+    let p45 = param(m, 45);
+    let p46 = param(m, 46);
+    let p48 = param(m, 48);
+    let p49 = param(m, 49);
+    let p50 = param(m, 50);
+    let p52 = param(m, 52);
+    let p53 = param(m, 53);
+    let p54 = param(m, 54);
+    let p56 = param(m, 56);
+    let p57 = param(m, 57);
+    let p58 = param(m, 58);
+    let p60 = param(m, 60);
+    let p61 = param(m, 61);
+    let p62 = param(m, 62);
+    let p64 = param(m, 64);
+    let p65 = param(m, 65);
+    let p66 = param(m, 66);
+    let p68 = param(m, 68);
+    let p69 = param(m, 69);
+    let p70 = param(m, 70);
+    let p72 = param(m, 72);
+    let p73 = param(m, 73);
+    let p74 = param(m, 74);
+    let p76 = param(m, 76);
+    let p77 = param(m, 77);
+    let p78 = param(m, 78);
+    let p80 = param(m, 80);
+    let p81 = param(m, 81);
+    let p82 = param(m, 82);
+    let p84 = param(m, 84);
+    let p85 = param(m, 85);
+    let p86 = param(m, 86);
+    let p88 = param(m, 88);
+    let p89 = param(m, 89);
+    let p90 = param(m, 90);
+    let p92 = param(m, 92);
+    let p93 = param(m, 93);
+    let p94 = param(m, 94);
+    let p96 = param(m, 96);
+    let p97 = param(m, 97);
+    let p98 = param(m, 98);
+    let p100 = param(m, 100);
+    let p101 = param(m, 101);
+    let p102 = param(m, 102);
+    let p104 = param(m, 104);
+    let p105 = param(m, 105);
+    let p106 = param(m, 106);
+    let p108 = param(m, 108);
+    let p109 = param(m, 109);
+    let p110 = param(m, 110);
+    let p112 = param(m, 112);
+    let p113 = param(m, 113);
+    let p114 = param(m, 114);
+    let p116 = param(m, 116);
+    let p117 = param(m, 117);
+    let p118 = param(m, 118);
+    let p120 = param(m, 120);
+    let p121 = param(m, 121);
+    let p122 = param(m, 122);
+    let p124 = param(m, 124);
+    let p125 = param(m, 125);
+    let p126 = param(m, 126);
+    let p128 = param(m, 128);
+    let p129 = param(m, 129);
+    let p130 = param(m, 130);
+    let p132 = param(m, 132);
+    let p133 = param(m, 133);
+    let p134 = param(m, 134);
+    let p136 = param(m, 136);
+    let p137 = param(m, 137);
+    let p138 = param(m, 138);
+    let p140 = param(m, 140);
+    let p141 = param(m, 141);
+    let p142 = param(m, 142);
+    let p144 = param(m, 144);
+    let p145 = param(m, 145);
+    let p146 = param(m, 146);
+    let p148 = param(m, 148);
+    let p149 = param(m, 149);
+    let p150 = param(m, 150);
+    let p151 = param(m, 151);
+    let p152 = param(m, 152);
+    let p153 = param(m, 153);
+    let p154 = param(m, 154);
+    let p155 = param(m, 155);
+    let mut ip: usize = 0;
+    let mut rb: i128 = 0;
+    // Generated words the program has changed, which only matter once an
+    // instruction using them is reached
+    let mut stale: Vec<usize> = Vec::new();
+    loop {
+        if !stale.is_empty() && stale.iter().any(|a| ip <= *a && *a < ip + 4) {
+            return fallback(m, inputs, outputs, ip, rb);
+        }
+        match ip {
+            0 => {
+                let a = or_fallback!(position(m, p1), fallback(m, inputs, outputs, 0, rb));
+                let b = or_fallback!(position(m, p2), fallback(m, inputs, outputs, 0, rb));
+                let c = param(m, 3);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 0, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 0, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 4;
+            }
+            4 => {
+                let a = or_fallback!(position(m, p5), fallback(m, inputs, outputs, 4, rb));
+                let b = or_fallback!(position(m, p6), fallback(m, inputs, outputs, 4, rb));
+                let c = p7;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 4, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 4, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 8;
+            }
+            8 => {
+                let a = or_fallback!(position(m, p9), fallback(m, inputs, outputs, 8, rb));
+                let b = or_fallback!(position(m, p10), fallback(m, inputs, outputs, 8, rb));
+                let c = p11;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 8, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 8, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 12;
+            }
+            12 => {
+                let a = or_fallback!(position(m, p13), fallback(m, inputs, outputs, 12, rb));
+                let b = or_fallback!(position(m, p14), fallback(m, inputs, outputs, 12, rb));
+                let c = p15;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 12, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 12, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 16;
+            }
+            16 => {
+                let a = or_fallback!(position(m, p17), fallback(m, inputs, outputs, 16, rb));
+                let b = or_fallback!(position(m, p18), fallback(m, inputs, outputs, 16, rb));
+                let c = param(m, 19);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 16, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 16, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 20;
+            }
+            20 => {
+                let a = or_fallback!(position(m, p21), fallback(m, inputs, outputs, 20, rb));
+                let b = or_fallback!(position(m, p22), fallback(m, inputs, outputs, 20, rb));
+                let c = param(m, 23);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 20, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 20, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 24;
+            }
+            24 => {
+                let a = or_fallback!(position(m, p25), fallback(m, inputs, outputs, 24, rb));
+                let b = or_fallback!(position(m, p26), fallback(m, inputs, outputs, 24, rb));
+                let c = param(m, 27);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 24, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 24, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 28;
+            }
+            28 => {
+                let a = or_fallback!(position(m, p29), fallback(m, inputs, outputs, 28, rb));
+                let b = or_fallback!(position(m, p30), fallback(m, inputs, outputs, 28, rb));
+                let c = param(m, 31);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 28, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 28, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 32;
+            }
+            32 => {
+                let a = or_fallback!(position(m, p33), fallback(m, inputs, outputs, 32, rb));
+                let b = or_fallback!(position(m, p34), fallback(m, inputs, outputs, 32, rb));
+                let c = param(m, 35);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 32, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 32, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 36;
+            }
+            36 => {
+                let a = or_fallback!(position(m, p37), fallback(m, inputs, outputs, 36, rb));
+                let b = or_fallback!(position(m, p38), fallback(m, inputs, outputs, 36, rb));
+                let c = param(m, 39);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 36, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 36, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 40;
+            }
+            40 => {
+                let a = or_fallback!(position(m, p41), fallback(m, inputs, outputs, 40, rb));
+                let b = or_fallback!(position(m, p42), fallback(m, inputs, outputs, 40, rb));
+                let c = param(m, 43);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 40, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 40, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 44;
+            }
+            44 => {
+                let a = or_fallback!(position(m, p45), fallback(m, inputs, outputs, 44, rb));
+                let b = or_fallback!(position(m, p46), fallback(m, inputs, outputs, 44, rb));
+                let c = param(m, 47);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 44, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 44, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 48;
+            }
+            48 => {
+                let a = or_fallback!(position(m, p49), fallback(m, inputs, outputs, 48, rb));
+                let b = or_fallback!(position(m, p50), fallback(m, inputs, outputs, 48, rb));
+                let c = param(m, 51);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 48, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 48, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 52;
+            }
+            52 => {
+                let a = or_fallback!(position(m, p53), fallback(m, inputs, outputs, 52, rb));
+                let b = or_fallback!(position(m, p54), fallback(m, inputs, outputs, 52, rb));
+                let c = param(m, 55);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 52, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 52, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 56;
+            }
+            56 => {
+                let a = or_fallback!(position(m, p57), fallback(m, inputs, outputs, 56, rb));
+                let b = or_fallback!(position(m, p58), fallback(m, inputs, outputs, 56, rb));
+                let c = param(m, 59);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 56, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 56, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 60;
+            }
+            60 => {
+                let a = or_fallback!(position(m, p61), fallback(m, inputs, outputs, 60, rb));
+                let b = or_fallback!(position(m, p62), fallback(m, inputs, outputs, 60, rb));
+                let c = param(m, 63);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 60, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 60, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 64;
+            }
+            64 => {
+                let a = or_fallback!(position(m, p65), fallback(m, inputs, outputs, 64, rb));
+                let b = or_fallback!(position(m, p66), fallback(m, inputs, outputs, 64, rb));
+                let c = param(m, 67);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 64, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 64, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 68;
+            }
+            68 => {
+                let a = or_fallback!(position(m, p69), fallback(m, inputs, outputs, 68, rb));
+                let b = or_fallback!(position(m, p70), fallback(m, inputs, outputs, 68, rb));
+                let c = param(m, 71);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 68, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 68, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 72;
+            }
+            72 => {
+                let a = or_fallback!(position(m, p73), fallback(m, inputs, outputs, 72, rb));
+                let b = or_fallback!(position(m, p74), fallback(m, inputs, outputs, 72, rb));
+                let c = param(m, 75);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 72, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 72, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 76;
+            }
+            76 => {
+                let a = or_fallback!(position(m, p77), fallback(m, inputs, outputs, 76, rb));
+                let b = or_fallback!(position(m, p78), fallback(m, inputs, outputs, 76, rb));
+                let c = param(m, 79);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 76, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 76, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 80;
+            }
+            80 => {
+                let a = or_fallback!(position(m, p81), fallback(m, inputs, outputs, 80, rb));
+                let b = or_fallback!(position(m, p82), fallback(m, inputs, outputs, 80, rb));
+                let c = param(m, 83);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 80, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 80, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 84;
+            }
+            84 => {
+                let a = or_fallback!(position(m, p85), fallback(m, inputs, outputs, 84, rb));
+                let b = or_fallback!(position(m, p86), fallback(m, inputs, outputs, 84, rb));
+                let c = param(m, 87);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 84, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 84, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 88;
+            }
+            88 => {
+                let a = or_fallback!(position(m, p89), fallback(m, inputs, outputs, 88, rb));
+                let b = or_fallback!(position(m, p90), fallback(m, inputs, outputs, 88, rb));
+                let c = param(m, 91);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 88, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 88, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 92;
+            }
+            92 => {
+                let a = or_fallback!(position(m, p93), fallback(m, inputs, outputs, 92, rb));
+                let b = or_fallback!(position(m, p94), fallback(m, inputs, outputs, 92, rb));
+                let c = param(m, 95);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 92, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 92, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 96;
+            }
+            96 => {
+                let a = or_fallback!(position(m, p97), fallback(m, inputs, outputs, 96, rb));
+                let b = or_fallback!(position(m, p98), fallback(m, inputs, outputs, 96, rb));
+                let c = param(m, 99);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 96, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 96, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 100;
+            }
+            100 => {
+                let a = or_fallback!(position(m, p101), fallback(m, inputs, outputs, 100, rb));
+                let b = or_fallback!(position(m, p102), fallback(m, inputs, outputs, 100, rb));
+                let c = param(m, 103);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 100, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 100, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 104;
+            }
+            104 => {
+                let a = or_fallback!(position(m, p105), fallback(m, inputs, outputs, 104, rb));
+                let b = or_fallback!(position(m, p106), fallback(m, inputs, outputs, 104, rb));
+                let c = param(m, 107);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 104, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 104, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 108;
+            }
+            108 => {
+                let a = or_fallback!(position(m, p109), fallback(m, inputs, outputs, 108, rb));
+                let b = or_fallback!(position(m, p110), fallback(m, inputs, outputs, 108, rb));
+                let c = param(m, 111);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 108, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 108, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 112;
+            }
+            112 => {
+                let a = or_fallback!(position(m, p113), fallback(m, inputs, outputs, 112, rb));
+                let b = or_fallback!(position(m, p114), fallback(m, inputs, outputs, 112, rb));
+                let c = param(m, 115);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 112, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 112, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 116;
+            }
+            116 => {
+                let a = or_fallback!(position(m, p117), fallback(m, inputs, outputs, 116, rb));
+                let b = or_fallback!(position(m, p118), fallback(m, inputs, outputs, 116, rb));
+                let c = param(m, 119);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 116, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 116, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 120;
+            }
+            120 => {
+                let a = or_fallback!(position(m, p121), fallback(m, inputs, outputs, 120, rb));
+                let b = or_fallback!(position(m, p122), fallback(m, inputs, outputs, 120, rb));
+                let c = param(m, 123);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 120, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 120, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 124;
+            }
+            124 => {
+                let a = or_fallback!(position(m, p125), fallback(m, inputs, outputs, 124, rb));
+                let b = or_fallback!(position(m, p126), fallback(m, inputs, outputs, 124, rb));
+                let c = param(m, 127);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 124, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 124, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 128;
+            }
+            128 => {
+                let a = or_fallback!(position(m, p129), fallback(m, inputs, outputs, 128, rb));
+                let b = or_fallback!(position(m, p130), fallback(m, inputs, outputs, 128, rb));
+                let c = param(m, 131);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 128, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 128, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 132;
+            }
+            132 => {
+                let a = or_fallback!(position(m, p133), fallback(m, inputs, outputs, 132, rb));
+                let b = or_fallback!(position(m, p134), fallback(m, inputs, outputs, 132, rb));
+                let c = param(m, 135);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 132, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 132, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 136;
+            }
+            136 => {
+                let a = or_fallback!(position(m, p137), fallback(m, inputs, outputs, 136, rb));
+                let b = or_fallback!(position(m, p138), fallback(m, inputs, outputs, 136, rb));
+                let c = param(m, 139);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 136, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 136, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 140;
+            }
+            140 => {
+                let a = or_fallback!(position(m, p141), fallback(m, inputs, outputs, 140, rb));
+                let b = or_fallback!(position(m, p142), fallback(m, inputs, outputs, 140, rb));
+                let c = param(m, 143);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 140, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 140, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 144;
+            }
+            144 => {
+                let a = or_fallback!(position(m, p145), fallback(m, inputs, outputs, 144, rb));
+                let b = or_fallback!(position(m, p146), fallback(m, inputs, outputs, 144, rb));
+                let c = param(m, 147);
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 144, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 144, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 148;
+            }
+            148 => {
+                let a = or_fallback!(position(m, p149), fallback(m, inputs, outputs, 148, rb));
+                let b = or_fallback!(position(m, p150), fallback(m, inputs, outputs, 148, rb));
+                let c = p151;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 148, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 148, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 152;
+            }
+            152 => {
+                return;
+            }
+            _ => return fallback(m, inputs, outputs, ip, rb),
+        }
+    }
+}
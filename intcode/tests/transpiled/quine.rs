@@ -0,0 +1,179 @@
+// Generated by intcode-transpile, do not edit
+#![allow(clippy::all, dead_code, unused_mut, unused_variables, unreachable_code)]
+use intcode::IntcodeComputer;
+
+pub const PROGRAM: [i128; 16] = [109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99];
+
+/// Addresses from here on are left to the interpreter, which knows the
+/// machine's memory limit
+const MAX_MEMORY: usize = 1 << 24;
+
+/// Unwraps a value, or returns the result of handing off to the interpreter
+macro_rules! or_fallback {
+    ($value:expr, $fallback:expr) => {
+        match $value {
+            Some(value) => value,
+            None => return $fallback,
+        }
+    };
+}
+
+#[inline(always)]
+fn param(m: &[i128], address: usize) -> i128 {
+    *m.get(address).unwrap_or(&0)
+}
+
+#[inline(always)]
+fn extend(m: &mut Vec<i128>, len: usize) {
+    if m.len() < len {
+        grow(m, len);
+    }
+}
+
+/// Kept out of line so the common case of memory already being big enough
+/// stays small
+#[cold]
+#[inline(never)]
+fn grow(m: &mut Vec<i128>, len: usize) {
+    m.resize(len, 0);
+}
+
+/// Address the generated code can use directly, if any
+#[inline(always)]
+fn address(arg: i128) -> Option<usize> {
+    if arg < 0 || arg >= MAX_MEMORY as i128 {
+        None
+    } else {
+        Some(arg as usize)
+    }
+}
+
+#[inline(always)]
+fn position(m: &mut Vec<i128>, arg: i128) -> Option<i128> {
+    if arg < 0 {
+        return Some(0);
+    }
+    let address = address(arg)?;
+    extend(m, address + 1);
+    Some(m[address])
+}
+
+#[inline(always)]
+fn relative(m: &mut Vec<i128>, rb: i128, arg: i128, dest: bool) -> Option<i128> {
+    let target = rb.checked_add(arg)?;
+    let address = address(target)?;
+    extend(m, address + 1);
+    if dest {
+        Some(target)
+    } else {
+        Some(m[address])
+    }
+}
+
+/// Writes a value, returning true if it changed one of the generated words
+#[inline(always)]
+fn write(m: &mut Vec<i128>, address: usize, value: i128) -> bool {
+    extend(m, address + 1);
+    let changed = m[address] != value;
+    m[address] = value;
+    changed && is_code(address)
+}
+
+/// Finishes the run on the interpreter from the given state
+#[cold]
+#[inline(never)]
+fn fallback(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>, ip: usize, rb: i128) {
+    let mut computer = IntcodeComputer::new(std::mem::take(m), std::mem::take(inputs), false);
+    computer.outputs = std::mem::take(outputs);
+    computer.index = ip;
+    computer.relative_base = rb;
+    computer.run();
+    *m = computer.intcodes.to_vec();
+    *inputs = computer.inputs;
+    *outputs = computer.outputs;
+}
+
+/// Addresses of the words the match arms were generated from
+fn is_code(address: usize) -> bool {
+    matches!(address, 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | 11 | 12 | 13 | 14 | 15 | 16 | 17 | 18)
+}
+
+/// Runs until the program halts, starting from `m` as memory
+pub fn run(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>) {
+    let p0 = param(m, 0);
+    let p1 = param(m, 1);
+    let p2 = param(m, 2);
+    let p3 = param(m, 3);
+    let p4 = param(m, 4);
+    let p5 = param(m, 5);
+    let p6 = param(m, 6);
+    let p7 = param(m, 7);
+    let p8 = param(m, 8);
+    let p9 = param(m, 9);
+    let p10 = param(m, 10);
+    let p11 = param(m, 11);
+    let p12 = param(m, 12);
+    let p13 = param(m, 13);
+    let p14 = param(m, 14);
+    let p15 = param(m, 15);
+    let p16 = param(m, 16);
+    let p17 = param(m, 17);
+    let p18 = param(m, 18);
+    let mut ip: usize = 0;
+    let mut rb: i128 = 0;
+    // Generated words the program has changed, which only matter once an
+    // instruction using them is reached
+    let mut stale: Vec<usize> = Vec::new();
+    loop {
+        if !stale.is_empty() && stale.iter().any(|a| ip <= *a && *a < ip + 4) {
+            return fallback(m, inputs, outputs, ip, rb);
+        }
+        match ip {
+            0 => {
+                let a = p1;
+                let b = or_fallback!(position(m, p2), fallback(m, inputs, outputs, 0, rb));
+                let c = p3;
+                rb = or_fallback!(rb.checked_add(a), fallback(m, inputs, outputs, 0, rb));
+                ip = 2;
+            }
+            2 => {
+                let a = or_fallback!(relative(m, rb, p3, false), fallback(m, inputs, outputs, 2, rb));
+                let b = or_fallback!(position(m, p4), fallback(m, inputs, outputs, 2, rb));
+                let c = p5;
+                outputs.push(a);
+                ip = 4;
+            }
+            4 => {
+                let a = or_fallback!(position(m, p5), fallback(m, inputs, outputs, 4, rb));
+                let b = p6;
+                let c = p7;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 4, rb));
+                let value = or_fallback!(a.checked_add(b), fallback(m, inputs, outputs, 4, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 8;
+            }
+            8 => {
+                let a = or_fallback!(position(m, p9), fallback(m, inputs, outputs, 8, rb));
+                let b = p10;
+                let c = p11;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 8, rb));
+                if write(m, dest, (a == b) as i128) {
+                    stale.push(dest);
+                }
+                ip = 12;
+            }
+            12 => {
+                let a = or_fallback!(position(m, p13), fallback(m, inputs, outputs, 12, rb));
+                let b = p14;
+                let c = p15;
+                ip = if a == 0 { or_fallback!(address(b), fallback(m, inputs, outputs, 12, rb)) } else { 15 };
+            }
+            15 => {
+                return;
+            }
+            _ => return fallback(m, inputs, outputs, ip, rb),
+        }
+    }
+}
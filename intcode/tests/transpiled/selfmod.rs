@@ -0,0 +1,131 @@
+// Generated by intcode-transpile, do not edit
+#![allow(clippy::all, dead_code, unused_mut, unused_variables, unreachable_code)]
+use intcode::IntcodeComputer;
+
+pub const PROGRAM: [i128; 5] = [1002, 4, 3, 4, 33];
+
+/// Addresses from here on are left to the interpreter, which knows the
+/// machine's memory limit
+const MAX_MEMORY: usize = 1 << 24;
+
+/// Unwraps a value, or returns the result of handing off to the interpreter
+macro_rules! or_fallback {
+    ($value:expr, $fallback:expr) => {
+        match $value {
+            Some(value) => value,
+            None => return $fallback,
+        }
+    };
+}
+
+#[inline(always)]
+fn param(m: &[i128], address: usize) -> i128 {
+    *m.get(address).unwrap_or(&0)
+}
+
+#[inline(always)]
+fn extend(m: &mut Vec<i128>, len: usize) {
+    if m.len() < len {
+        grow(m, len);
+    }
+}
+
+/// Kept out of line so the common case of memory already being big enough
+/// stays small
+#[cold]
+#[inline(never)]
+fn grow(m: &mut Vec<i128>, len: usize) {
+    m.resize(len, 0);
+}
+
+/// Address the generated code can use directly, if any
+#[inline(always)]
+fn address(arg: i128) -> Option<usize> {
+    if arg < 0 || arg >= MAX_MEMORY as i128 {
+        None
+    } else {
+        Some(arg as usize)
+    }
+}
+
+#[inline(always)]
+fn position(m: &mut Vec<i128>, arg: i128) -> Option<i128> {
+    if arg < 0 {
+        return Some(0);
+    }
+    let address = address(arg)?;
+    extend(m, address + 1);
+    Some(m[address])
+}
+
+#[inline(always)]
+fn relative(m: &mut Vec<i128>, rb: i128, arg: i128, dest: bool) -> Option<i128> {
+    let target = rb.checked_add(arg)?;
+    let address = address(target)?;
+    extend(m, address + 1);
+    if dest {
+        Some(target)
+    } else {
+        Some(m[address])
+    }
+}
+
+/// Writes a value, returning true if it changed one of the generated words
+#[inline(always)]
+fn write(m: &mut Vec<i128>, address: usize, value: i128) -> bool {
+    extend(m, address + 1);
+    let changed = m[address] != value;
+    m[address] = value;
+    changed && is_code(address)
+}
+
+/// Finishes the run on the interpreter from the given state
+#[cold]
+#[inline(never)]
+fn fallback(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>, ip: usize, rb: i128) {
+    let mut computer = IntcodeComputer::new(std::mem::take(m), std::mem::take(inputs), false);
+    computer.outputs = std::mem::take(outputs);
+    computer.index = ip;
+    computer.relative_base = rb;
+    computer.run();
+    *m = computer.intcodes.to_vec();
+    *inputs = computer.inputs;
+    *outputs = computer.outputs;
+}
+
+/// Addresses of the words the match arms were generated from
+fn is_code(address: usize) -> bool {
+    matches!(address, 0 | 1 | 2 | 3)
+}
+
+/// Runs until the program halts, starting from `m` as memory
+pub fn run(m: &mut Vec<i128>, inputs: &mut Vec<i128>, outputs: &mut Vec<i128>) {
+    let p0 = param(m, 0);
+    let p1 = param(m, 1);
+    let p2 = param(m, 2);
+    let p3 = param(m, 3);
+    let mut ip: usize = 0;
+    let mut rb: i128 = 0;
+    // Generated words the program has changed, which only matter once an
+    // instruction using them is reached
+    let mut stale: Vec<usize> = Vec::new();
+    loop {
+        if !stale.is_empty() && stale.iter().any(|a| ip <= *a && *a < ip + 4) {
+            return fallback(m, inputs, outputs, ip, rb);
+        }
+        match ip {
+            0 => {
+                let a = or_fallback!(position(m, p1), fallback(m, inputs, outputs, 0, rb));
+                let b = p2;
+                let c = p3;
+                let dest = or_fallback!(address(c), fallback(m, inputs, outputs, 0, rb));
+                let value = or_fallback!(a.checked_mul(b), fallback(m, inputs, outputs, 0, rb));
+                if write(m, dest, value) {
+                    stale.push(dest);
+                }
+                ip = 4;
+            }
+            _ => return fallback(m, inputs, outputs, ip, rb),
+        }
+    }
+}